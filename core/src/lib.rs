@@ -0,0 +1,181 @@
+// Enigma without std and allocations, for microcontrollers. Parts have
+// fixed-size arrays for A-Z wirings, and the machine has const count of
+// rotors, so everything can live on the stack or in a static.
+//
+// `std` feature adds std::error::Error for errors and String conveniences,
+// the full simulator with custom alphabets and the command line tool is the
+// `enigma` crate.
+//
+// Rotors, reflectors and plugboards take capacity of the alphabet as const
+// parameter, A-Z by default. Besides A-Z letters they work with indexes of
+// any alphabet up to the capacity, that is how the `enigma` crate builds its
+// parts on top of these, and turns its rotors by `stepping`.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(
+    clippy::bool_comparison,
+    clippy::needless_arbitrary_self_type,
+    clippy::needless_return,
+    clippy::new_without_default,
+    clippy::single_match
+)]
+
+pub mod machine;
+pub mod models;
+pub mod plugboard;
+pub mod reflector;
+pub mod rotor;
+pub mod stepping;
+
+use core::fmt;
+
+pub const LETTERS: usize = 26;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    InvalidLetter(/*letter*/ char),
+    InvalidWiringSize(/*actual*/ usize),
+    MissedWiringLetter(/*missed*/ u8),
+    NotReciprocal(/*input*/ u8, /*output*/ u8),
+    UnknownModel,
+    InvalidCountOfPositions(/*actual*/ usize, /*expected*/ usize),
+    Duplicate(/*letter*/ u8),
+    SameLetters,
+    BufferTooSmall(/*required*/ usize),
+    // for parts built from indexes of other alphabets
+    InvalidIndex(/*index*/ u8),
+    DuplicateIndex(/*index*/ u8),
+    UnknownStepping,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidLetter(letter) => write!(f, "invalid letter: {}", letter),
+            Error::InvalidWiringSize(actual) => write!(f, "invalid wiring size: {}", actual),
+            Error::MissedWiringLetter(letter) => {
+                write!(f, "wiring doesn't have letter: {}", *letter as char)
+            }
+            Error::NotReciprocal(input, output) => write!(
+                f,
+                "reflector is not reciprocal: {} -> {}, but {} -> other",
+                *input as char, *output as char, *output as char
+            ),
+            Error::UnknownModel => write!(f, "unknown model"),
+            Error::InvalidCountOfPositions(actual, expected) => write!(
+                f,
+                "invalid count of positions: {}/{} (actual/expected)",
+                actual, expected
+            ),
+            Error::Duplicate(letter) => {
+                write!(
+                    f,
+                    "plugboard already have connection for: {}",
+                    *letter as char
+                )
+            }
+            Error::SameLetters => write!(f, "input and output of plug pair can not be same"),
+            Error::BufferTooSmall(required) => {
+                write!(f, "output buffer too small, required: {}", required)
+            }
+            Error::InvalidIndex(index) => write!(f, "invalid index: {}", index),
+            Error::DuplicateIndex(index) => {
+                write!(f, "plugboard already have connection for index: {}", index)
+            }
+            Error::UnknownStepping => write!(f, "unknown stepping"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+// index of A-Z letter, lowercase letters are accepted too
+pub fn index(letter: u8) -> Result<u8, Error> {
+    match letter {
+        b'A'..=b'Z' => return Ok(letter - b'A'),
+        b'a'..=b'z' => return Ok(letter - b'a'),
+        _ => return Err(Error::InvalidLetter(letter as char)),
+    }
+}
+
+pub fn letter(index: u8) -> u8 {
+    return b'A' + index;
+}
+
+// indexes of the wiring, like "EKMFLGDQVZNTOWYHXUSPAIBRCJ"
+pub(crate) fn parse_wiring(wiring: &str) -> Result<[u8; LETTERS], Error> {
+    let bytes = wiring.as_bytes();
+    if bytes.len() != LETTERS {
+        return Err(Error::InvalidWiringSize(wiring.chars().count()));
+    }
+
+    let mut outputs = [0; LETTERS];
+    let mut used = [false; LETTERS];
+    for (input, byte) in bytes.iter().enumerate() {
+        let output = index(*byte)?;
+        used[output as usize] = true;
+        outputs[input] = output;
+    }
+    if let Some(missed) = used.iter().position(|used| *used == false) {
+        return Err(Error::MissedWiringLetter(letter(missed as u8)));
+    }
+    return Ok(outputs);
+}
+
+// Outputs of wiring of an alphabet with `wiring.len()` letters, stored in
+// array of capacity L. Every index must be used once
+pub(crate) fn permutation<const L: usize>(wiring: &[u8]) -> Result<[u8; L], Error> {
+    if wiring.is_empty() || wiring.len() > L || L > u8::MAX as usize + 1 {
+        return Err(Error::InvalidWiringSize(wiring.len()));
+    }
+
+    let mut outputs = [0; L];
+    let mut used = [false; L];
+    for (input, output) in wiring.iter().enumerate() {
+        let index = *output as usize;
+        if index >= wiring.len() || used[index] {
+            return Err(Error::InvalidIndex(*output));
+        }
+        used[index] = true;
+        outputs[input] = *output;
+    }
+    return Ok(outputs);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index;
+    use crate::parse_wiring;
+    use crate::permutation;
+    use crate::Error;
+
+    #[test]
+    fn letters() {
+        assert_eq!(index(b'A'), Ok(0));
+        assert_eq!(index(b'z'), Ok(25));
+        assert_eq!(index(b'1'), Err(Error::InvalidLetter('1')));
+    }
+
+    #[test]
+    fn wirings() {
+        assert_eq!(parse_wiring("EKMFLGDQVZNTOWYHXUSPAIBRCJ").unwrap()[0], 4);
+        assert_eq!(parse_wiring("ABC"), Err(Error::InvalidWiringSize(3)));
+        assert_eq!(
+            parse_wiring("AKMFLGDQVZNTOWYHXUSPAIBRCJ"),
+            Err(Error::MissedWiringLetter(b'E'))
+        );
+        assert!(parse_wiring("ÄKMFLGDQVZNTOWYHXUSPAIBRCJ").is_err());
+    }
+
+    #[test]
+    fn permutations() {
+        assert_eq!(permutation::<4>(&[2, 0, 1]), Ok([2, 0, 1, 0]));
+        assert_eq!(permutation::<4>(&[]), Err(Error::InvalidWiringSize(0)));
+        assert_eq!(
+            permutation::<2>(&[2, 0, 1]),
+            Err(Error::InvalidWiringSize(3))
+        );
+        assert_eq!(permutation::<4>(&[2, 0, 2]), Err(Error::InvalidIndex(2)));
+        assert_eq!(permutation::<4>(&[3, 0, 1]), Err(Error::InvalidIndex(3)));
+    }
+}
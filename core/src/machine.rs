@@ -0,0 +1,285 @@
+use crate::index;
+use crate::letter;
+use crate::plugboard::PlugBoard;
+use crate::reflector::Reflector;
+use crate::rotor::Rotor;
+use crate::stepping::Stepping;
+use crate::Error;
+
+// Machine with N rotors from left to right, with ratchet stepping (see
+// Stepping). Every slot has pawl, like Enigma I (N = 3):
+//
+// let rotors = [Rotor::model("II")?, Rotor::model("IV")?, Rotor::model("V")?];
+// let mut enigma = Enigma::new(Reflector::model("B")?, rotors, PlugBoard::new());
+//
+// except the left slot of M4 (N = 4): thin rotor there, or thin reflector,
+// gives the left slot no pawl, so the thin rotor never turns:
+//
+// let rotors = [
+//     Rotor::model("beta")?,
+//     Rotor::model("I")?,
+//     Rotor::model("II")?,
+//     Rotor::model("III")?,
+// ];
+// let m4 = Enigma::new(Reflector::model("B-thin")?, rotors, PlugBoard::new());
+// assert_eq!(m4.pawls(), &[false, true, true, true]);
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Enigma<const N: usize> {
+    reflector: Reflector,
+    rotors: [Rotor; N],
+    pawls: [bool; N],
+    plugboard: PlugBoard,
+}
+
+impl<const N: usize> Enigma<N> {
+    pub fn new(reflector: Reflector, rotors: [Rotor; N], plugboard: PlugBoard) -> Self {
+        let mut pawls = [true; N];
+        let m4 = (N == 4 && reflector.is_thin()) || rotors.first().is_some_and(Rotor::is_thin);
+        if m4 {
+            pawls[0] = false;
+        }
+
+        return Self {
+            reflector,
+            rotors,
+            pawls,
+            plugboard,
+        };
+    }
+
+    pub fn pawls(self: &Self) -> &[bool; N] {
+        return &self.pawls;
+    }
+
+    // rotors without pawl never turn, see `Enigma` for default layout
+    pub fn set_pawls(self: &mut Self, pawls: [bool; N]) {
+        self.pawls = pawls;
+    }
+
+    pub fn rotors(self: &Self) -> &[Rotor; N] {
+        return &self.rotors;
+    }
+
+    pub fn plugboard(self: &Self) -> &PlugBoard {
+        return &self.plugboard;
+    }
+
+    pub fn set_plugboard(self: &mut Self, plugboard: PlugBoard) {
+        self.plugboard = plugboard;
+    }
+
+    // letters of current segments, from left to right
+    pub fn positions(self: &Self) -> [u8; N] {
+        let mut positions = [0; N];
+        for (position, rotor) in positions.iter_mut().zip(self.rotors.iter()) {
+            *position = rotor.position();
+        }
+        return positions;
+    }
+
+    // positions like "BLA", rotors are not changed on error
+    pub fn set_positions(self: &mut Self, positions: &str) -> Result<(), Error> {
+        let mut rotors = self.rotors;
+        Self::set_each(&mut rotors, positions, Rotor::set_position)?;
+        self.rotors = rotors;
+        return Ok(());
+    }
+
+    pub fn ring_offsets(self: &Self) -> [u8; N] {
+        let mut offsets = [0; N];
+        for (offset, rotor) in offsets.iter_mut().zip(self.rotors.iter()) {
+            *offset = rotor.ring_offset();
+        }
+        return offsets;
+    }
+
+    pub fn set_ring_offsets(self: &mut Self, offsets: &str) -> Result<(), Error> {
+        let mut rotors = self.rotors;
+        Self::set_each(&mut rotors, offsets, Rotor::set_ring_offset)?;
+        self.rotors = rotors;
+        return Ok(());
+    }
+
+    fn set_each(
+        rotors: &mut [Rotor; N],
+        letters: &str,
+        set: fn(&mut Rotor, u8) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let count = letters.chars().count();
+        if count != N {
+            return Err(Error::InvalidCountOfPositions(count, N));
+        }
+        for (rotor, letter) in rotors.iter_mut().zip(letters.chars()) {
+            if letter.is_ascii() == false {
+                return Err(Error::InvalidLetter(letter));
+            }
+            set(rotor, letter as u8)?;
+        }
+        return Ok(());
+    }
+
+    // key press: rotors turn before the signal goes through them
+    pub fn press(self: &mut Self, key: u8) -> Result<u8, Error> {
+        let mut signal = self.plugboard.swap(index(key)?);
+        Stepping::Ratchet.advance(&mut self.rotors, &self.pawls);
+
+        for rotor in self.rotors.iter().rev() {
+            signal = rotor.forward(signal);
+        }
+        signal = self.reflector.reflect(signal);
+        for rotor in self.rotors.iter() {
+            signal = rotor.backward(signal);
+        }
+
+        return Ok(letter(self.plugboard.swap(signal)));
+    }
+
+    // Encrypts A-Z letters of input into output, other bytes are skipped.
+    // Returns count of written bytes
+    pub fn crypt(self: &mut Self, input: &[u8], output: &mut [u8]) -> Result<usize, Error> {
+        let required = input.iter().filter(|byte| index(**byte).is_ok()).count();
+        if required > output.len() {
+            return Err(Error::BufferTooSmall(required));
+        }
+
+        let mut written = 0;
+        for byte in input.iter() {
+            if let Ok(out) = self.press(*byte) {
+                output[written] = out;
+                written += 1;
+            }
+        }
+        return Ok(written);
+    }
+
+    // encrypts A-Z letters in place, other bytes are not changed
+    pub fn crypt_in_place(self: &mut Self, buffer: &mut [u8]) {
+        for byte in buffer.iter_mut() {
+            if let Ok(out) = self.press(*byte) {
+                *byte = out;
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn encrypt(self: &mut Self, text: &str) -> String {
+        let mut output = vec![0; text.len()];
+        let written = self.crypt(text.as_bytes(), &mut output).expect("enough");
+        output.truncate(written);
+        return String::from_utf8(output).expect("A-Z letters");
+    }
+
+    #[cfg(feature = "std")]
+    pub fn positions_string(self: &Self) -> String {
+        return self.positions().iter().map(|byte| *byte as char).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::machine::Enigma;
+    use crate::plugboard::PlugBoard;
+    use crate::reflector::Reflector;
+    use crate::rotor::Rotor;
+    use crate::Error;
+
+    fn enigma_i(rotors: [&str; 3]) -> Enigma<3> {
+        return Enigma::new(
+            Reflector::model("B").unwrap(),
+            rotors.map(|name| Rotor::model(name).unwrap()),
+            PlugBoard::new(),
+        );
+    }
+
+    #[test]
+    fn crypt() {
+        let mut enigma = enigma_i(["I", "II", "III"]);
+        let mut output = [0; 5];
+        assert_eq!(enigma.crypt(b"AAAAA", &mut output), Ok(5));
+        assert_eq!(&output, b"BDZGO");
+        assert_eq!(&enigma.positions(), b"AAF");
+
+        enigma.set_positions("AAA").unwrap();
+        let mut buffer = *b"BD-ZGO";
+        enigma.crypt_in_place(&mut buffer);
+        assert_eq!(&buffer, b"AA-AAA");
+
+        assert_eq!(
+            enigma.crypt(b"AAAAA", &mut output[..2]),
+            Err(Error::BufferTooSmall(5))
+        );
+    }
+
+    #[test]
+    fn double_step() {
+        let mut enigma = enigma_i(["I", "II", "III"]);
+        enigma.set_positions("ADU").unwrap();
+        let mut output = [0; 3];
+        enigma.crypt(b"AAA", &mut output).unwrap();
+        assert_eq!(&enigma.positions(), b"BFX");
+    }
+
+    #[test]
+    fn pawls() {
+        let rotors = ["beta", "I", "II", "III"].map(|name| Rotor::model(name).unwrap());
+        let mut enigma = Enigma::new(
+            Reflector::model("B-thin").unwrap(),
+            rotors,
+            PlugBoard::new(),
+        );
+        assert_eq!(enigma.pawls(), &[false, true, true, true]);
+        enigma.set_positions("AQEV").unwrap();
+        let mut output = [0; 1];
+        enigma.crypt(b"A", &mut output).unwrap();
+        assert_eq!(&enigma.positions(), b"ARFW");
+
+        enigma.set_pawls([true; 4]);
+        enigma.set_positions("AQEV").unwrap();
+        enigma.crypt(b"A", &mut output).unwrap();
+        assert_eq!(&enigma.positions(), b"BRFW");
+
+        // thin reflector makes the left slot thin too
+        let rotors = ["I", "II", "III", "IV"].map(|name| Rotor::model(name).unwrap());
+        let enigma = Enigma::new(
+            Reflector::model("C-thin").unwrap(),
+            rotors,
+            PlugBoard::new(),
+        );
+        assert_eq!(enigma.pawls(), &[false, true, true, true]);
+    }
+
+    // Barbarossa, 7 July 1941
+    #[test]
+    fn settings() {
+        let mut enigma = enigma_i(["II", "IV", "V"]);
+        enigma.set_ring_offsets("BUL").unwrap();
+        enigma.set_positions("BLA").unwrap();
+        enigma.set_plugboard(PlugBoard::with_pairs("AV BS CG DL FU HZ IN KM OW RX").unwrap());
+
+        let mut output = [0; 32];
+        let written = enigma.crypt(b"EDPUD NRGYS", &mut output).unwrap();
+        assert_eq!(&output[..written], b"AUFKLXABTE");
+        assert_eq!(&enigma.ring_offsets(), b"BUL");
+
+        assert_eq!(
+            enigma.set_positions("AB"),
+            Err(Error::InvalidCountOfPositions(2, 3))
+        );
+        assert_eq!(enigma.set_positions("AB1"), Err(Error::InvalidLetter('1')));
+        // count of letters, not bytes
+        assert_eq!(enigma.set_positions("ÄB1"), Err(Error::InvalidLetter('Ä')));
+        assert_eq!(
+            enigma.set_positions("ÄB"),
+            Err(Error::InvalidCountOfPositions(2, 3))
+        );
+        assert_eq!(&enigma.positions(), b"BLK");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn strings() {
+        let mut enigma = enigma_i(["I", "II", "III"]);
+        assert_eq!(enigma.encrypt("Hello, world!"), "ILBDAAMTAZ");
+        assert_eq!(enigma.positions_string(), "AAK");
+    }
+}
@@ -0,0 +1,55 @@
+// Wirings of historical rotors and reflectors, used by the `enigma` crate too
+
+pub const ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+// for rotors
+pub const RING_I: &str = "EKMFLGDQVZNTOWYHXUSPAIBRCJ";
+pub const RING_II: &str = "AJDKSIRUXBLHWTMCQGZNPYFVOE";
+pub const RING_III: &str = "BDFHJLCPRTXVZNYEIWGAKMUSQO";
+pub const RING_IV: &str = "ESOVPZJAYQUIRHXLNFTGKDCMWB";
+pub const RING_V: &str = "VZBRGITYUPSDNHLXAWMJQOFECK";
+pub const RING_VI: &str = "JPGVOUMFYQBENHZRDKASXLICTW";
+pub const RING_VII: &str = "NZJHGRCXMYSWBOUFAIVLPEKQDT";
+pub const RING_VIII: &str = "FKQHTLXOCBJSPDZRAMEWNIUYGV";
+// thin rotors of M4, placed between reflector and left rotor
+pub const RING_BETA: &str = "LEYJVCNIXWPBQMDRTAKZGFUHOS";
+pub const RING_GAMMA: &str = "FSOKANUERHMBTIYCWLQPZXVGJD";
+// for reflectors
+pub const RING_A: &str = "EJMZALYXVBWFCRQUONTSPIKHGD";
+pub const RING_B: &str = "YRUHQSLDPXNGOKMIEBFZCWVJAT";
+pub const RING_C: &str = "FVPJIAOYEDRZXWGCTKUQSBNMHL";
+// thin reflectors of M4
+pub const RING_B_THIN: &str = "ENKQAUYWJICOPBLMDXZVFTHRGS";
+pub const RING_C_THIN: &str = "RDOBJNTKVEHMLFCWZAXGYIPSUQ";
+
+pub const NOTCH_I: &str = "Q";
+pub const NOTCH_II: &str = "E";
+pub const NOTCH_III: &str = "V";
+pub const NOTCH_IV: &str = "J";
+pub const NOTCH_V: &str = "Z";
+pub const NOTCH_VI: &str = "ZM";
+pub const NOTCH_VII: &str = "ZM";
+pub const NOTCH_VIII: &str = "ZM";
+pub const NOTCH_BETA: &str = "";
+pub const NOTCH_GAMMA: &str = "";
+
+// built-in models: name, wiring and notches
+pub const ROTORS: [(&str, &str, &str); 10] = [
+    ("I", RING_I, NOTCH_I),
+    ("II", RING_II, NOTCH_II),
+    ("III", RING_III, NOTCH_III),
+    ("IV", RING_IV, NOTCH_IV),
+    ("V", RING_V, NOTCH_V),
+    ("VI", RING_VI, NOTCH_VI),
+    ("VII", RING_VII, NOTCH_VII),
+    ("VIII", RING_VIII, NOTCH_VIII),
+    ("BETA", RING_BETA, NOTCH_BETA),
+    ("GAMMA", RING_GAMMA, NOTCH_GAMMA),
+];
+pub const REFLECTORS: [(&str, &str); 5] = [
+    ("A", RING_A),
+    ("B", RING_B),
+    ("C", RING_C),
+    ("B-THIN", RING_B_THIN),
+    ("C-THIN", RING_C_THIN),
+];
@@ -0,0 +1,158 @@
+use crate::index;
+use crate::letter;
+use crate::Error;
+use crate::LETTERS;
+
+// cables swap letters, not connected letters go through as is. Board for
+// alphabet of up to L letters, A-Z by default, see Rotor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlugBoard<const L: usize = LETTERS> {
+    outputs: [u8; L],
+    size: usize,
+}
+
+impl<const L: usize> PlugBoard<L> {
+    // board without cables for alphabet of `size` letters
+    pub fn with_size(size: usize) -> Result<Self, Error> {
+        if size == 0 || size > L || L > u8::MAX as usize + 1 {
+            return Err(Error::InvalidWiringSize(size));
+        }
+
+        let mut outputs = [0; L];
+        for (input, output) in outputs.iter_mut().enumerate() {
+            *output = input as u8;
+        }
+        return Ok(Self { outputs, size });
+    }
+
+    // connect letters by indexes
+    pub fn plug(self: &mut Self, input: u8, output: u8) -> Result<(), Error> {
+        for index in [input, output] {
+            if index as usize >= self.size {
+                return Err(Error::InvalidIndex(index));
+            }
+        }
+        if input == output {
+            return Err(Error::SameLetters);
+        }
+        for index in [input, output] {
+            if self.outputs[index as usize] != index {
+                return Err(Error::DuplicateIndex(index));
+            }
+        }
+
+        self.outputs[input as usize] = output;
+        self.outputs[output as usize] = input;
+        return Ok(());
+    }
+
+    pub fn swap(self: &Self, input: u8) -> u8 {
+        return self.outputs[input as usize];
+    }
+
+    pub fn cables(self: &Self) -> usize {
+        return self.outputs[..self.size]
+            .iter()
+            .enumerate()
+            .filter(|(input, output)| *input != **output as usize)
+            .count()
+            / 2;
+    }
+}
+
+impl PlugBoard {
+    pub fn new() -> Self {
+        return Self::with_size(LETTERS).expect("A-Z fits");
+    }
+
+    // pairs like "AV BS CG", with same separators as parse_cables of the
+    // `enigma` crate: whitespaces, commas and hyphens, pairs can go without
+    // separator too. Plugboard is not changed on error
+    pub fn with_pairs(pairs: &str) -> Result<Self, Error> {
+        let mut board = Self::new();
+        let mut first: Option<char> = None;
+        for ch in pairs.chars() {
+            if ch.is_whitespace() || ch == ',' || ch == '-' {
+                match first {
+                    Some(first) => return Err(Error::InvalidLetter(first)),
+                    None => continue,
+                }
+            }
+            match first.take() {
+                None => first = Some(ch),
+                Some(first) => board.connect_chars(first, ch)?,
+            }
+        }
+        if let Some(first) = first {
+            return Err(Error::InvalidLetter(first));
+        }
+        return Ok(board);
+    }
+
+    pub fn connect(self: &mut Self, first: u8, second: u8) -> Result<(), Error> {
+        let (input, output) = (index(first)?, index(second)?);
+        match self.plug(input, output) {
+            Err(Error::DuplicateIndex(index)) => return Err(Error::Duplicate(letter(index))),
+            other => return other,
+        }
+    }
+
+    fn connect_chars(self: &mut Self, first: char, second: char) -> Result<(), Error> {
+        for ch in [first, second] {
+            if ch.is_ascii() == false {
+                return Err(Error::InvalidLetter(ch));
+            }
+        }
+        return self.connect(first as u8, second as u8);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plugboard::PlugBoard;
+    use crate::Error;
+
+    #[test]
+    fn plugboard() {
+        let board = PlugBoard::with_pairs("AV BS,CG").unwrap();
+        assert_eq!(board.swap(0), 21);
+        assert_eq!(board.swap(21), 0);
+        assert_eq!(board.swap(3), 3);
+        assert_eq!(board.cables(), 3);
+
+        assert_eq!(PlugBoard::with_pairs("AV AB"), Err(Error::Duplicate(b'A')));
+        assert_eq!(PlugBoard::with_pairs("AA"), Err(Error::SameLetters));
+        assert_eq!(PlugBoard::with_pairs("AVB"), Err(Error::InvalidLetter('B')));
+        assert_eq!(PlugBoard::with_pairs("A1"), Err(Error::InvalidLetter('1')));
+        assert_eq!(PlugBoard::with_pairs("AÄ"), Err(Error::InvalidLetter('Ä')));
+    }
+
+    #[test]
+    fn separators() {
+        let board = PlugBoard::with_pairs("AV BS,CG").unwrap();
+        for pairs in ["AV-BS-CG", "AVBSCG", "AV\tBS\nCG", " AV, BS - CG "] {
+            assert_eq!(PlugBoard::with_pairs(pairs), Ok(board));
+        }
+
+        // letters of one pair can't be split by separator
+        assert_eq!(PlugBoard::with_pairs("A V"), Err(Error::InvalidLetter('A')));
+        assert_eq!(
+            PlugBoard::with_pairs("AVB SC"),
+            Err(Error::InvalidLetter('B'))
+        );
+    }
+
+    #[test]
+    fn indexes() {
+        let mut board = PlugBoard::<8>::with_size(4).unwrap();
+        board.plug(0, 3).unwrap();
+        assert_eq!(board.swap(3), 0);
+        assert_eq!(board.cables(), 1);
+        assert_eq!(board.plug(3, 1), Err(Error::DuplicateIndex(3)));
+        assert_eq!(board.plug(1, 4), Err(Error::InvalidIndex(4)));
+        assert_eq!(
+            PlugBoard::<8>::with_size(9),
+            Err(Error::InvalidWiringSize(9))
+        );
+    }
+}
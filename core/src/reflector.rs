@@ -0,0 +1,92 @@
+use crate::letter;
+use crate::models::REFLECTORS;
+use crate::models::RING_B_THIN;
+use crate::models::RING_C_THIN;
+use crate::parse_wiring;
+use crate::permutation;
+use crate::Error;
+use crate::LETTERS;
+
+// reflector for alphabet of up to L letters, A-Z by default, see Rotor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reflector<const L: usize = LETTERS> {
+    outputs: [u8; L],
+}
+
+impl<const L: usize> Reflector<L> {
+    // wiring is output index for every input index, it must be reciprocal
+    pub fn with_indexes(wiring: &[u8]) -> Result<Self, Error> {
+        let outputs = permutation::<L>(wiring)?;
+        for (input, output) in wiring.iter().enumerate() {
+            if outputs[*output as usize] as usize != input {
+                return Err(Error::InvalidIndex(*output));
+            }
+        }
+        return Ok(Self { outputs });
+    }
+
+    pub fn reflect(self: &Self, input: u8) -> u8 {
+        return self.outputs[input as usize];
+    }
+}
+
+impl Reflector {
+    // wiring must be reciprocal, otherwise signal can't go back
+    pub fn new(wiring: &str) -> Result<Self, Error> {
+        let outputs = parse_wiring(wiring)?;
+        for (input, output) in outputs.iter().enumerate() {
+            if outputs[*output as usize] as usize != input {
+                return Err(Error::NotReciprocal(letter(input as u8), letter(*output)));
+            }
+        }
+        return Ok(Self { outputs });
+    }
+
+    // historical reflector by name, like "B" or "c-thin"
+    pub fn model(name: &str) -> Result<Self, Error> {
+        for (model, wiring) in REFLECTORS.iter() {
+            if model.eq_ignore_ascii_case(name) {
+                return Self::new(wiring);
+            }
+        }
+        return Err(Error::UnknownModel);
+    }
+
+    // thin reflector of M4, it leaves room for the thin rotor
+    pub fn is_thin(self: &Self) -> bool {
+        return [RING_B_THIN, RING_C_THIN]
+            .iter()
+            .any(|wiring| parse_wiring(wiring) == Ok(self.outputs));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reflector::Reflector;
+    use crate::Error;
+
+    #[test]
+    fn reflector() {
+        let reflector = Reflector::model("b").unwrap();
+        assert_eq!(reflector.reflect(0), 24);
+        assert_eq!(reflector.reflect(24), 0);
+        assert!(reflector.is_thin() == false);
+        assert!(Reflector::model("c-thin").unwrap().is_thin());
+
+        assert_eq!(
+            Reflector::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ"),
+            Err(Error::NotReciprocal(b'A', b'E'))
+        );
+        assert_eq!(Reflector::model("D"), Err(Error::UnknownModel));
+    }
+
+    #[test]
+    fn indexes() {
+        let reflector = Reflector::<8>::with_indexes(&[2, 3, 0, 1]).unwrap();
+        assert_eq!(reflector.reflect(1), 3);
+        assert_eq!(
+            Reflector::<8>::with_indexes(&[1, 2, 0]),
+            Err(Error::InvalidIndex(1))
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use crate::index;
+use crate::letter;
+use crate::models::RING_BETA;
+use crate::models::RING_GAMMA;
+use crate::models::ROTORS;
+use crate::parse_wiring;
+use crate::permutation;
+use crate::stepping::Wheel;
+use crate::Error;
+use crate::LETTERS;
+
+// Rotor for alphabet of up to L letters, A-Z by default. All values are
+// indexes of letters: wiring of the right side, segment under the window and
+// offset of the ring. The `enigma` crate keeps its rotors of any alphabet in
+// these
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rotor<const L: usize = LETTERS> {
+    forward: [u8; L],
+    backward: [u8; L],
+    notches: [bool; L],
+    size: usize,
+    position: u8,
+    ring_offset: u8,
+}
+
+impl<const L: usize> Rotor<L> {
+    // wiring is output index for every input index, its length is the size
+    // of the alphabet
+    pub fn with_indexes(wiring: &[u8], notches: &[u8]) -> Result<Self, Error> {
+        let forward = permutation::<L>(wiring)?;
+        let mut backward = [0; L];
+        for (input, output) in wiring.iter().enumerate() {
+            backward[*output as usize] = input as u8;
+        }
+
+        let mut rotor = Self {
+            forward,
+            backward,
+            notches: [false; L],
+            size: wiring.len(),
+            position: 0,
+            ring_offset: 0,
+        };
+        for notch in notches.iter() {
+            rotor.check(*notch)?;
+            rotor.notches[*notch as usize] = true;
+        }
+        return Ok(rotor);
+    }
+
+    // count of letters in the alphabet
+    pub fn size(self: &Self) -> usize {
+        return self.size;
+    }
+
+    // index of current segment
+    pub fn segment(self: &Self) -> u8 {
+        return self.position;
+    }
+
+    pub fn set_segment(self: &mut Self, segment: u8) -> Result<(), Error> {
+        self.position = self.check(segment)?;
+        return Ok(());
+    }
+
+    pub fn ring_segment(self: &Self) -> u8 {
+        return self.ring_offset;
+    }
+
+    pub fn set_ring_segment(self: &mut Self, segment: u8) -> Result<(), Error> {
+        self.ring_offset = self.check(segment)?;
+        return Ok(());
+    }
+
+    pub fn at_notch(self: &Self) -> bool {
+        return self.notches[self.position as usize];
+    }
+
+    // return true if need turnover next rotor (was at notch position)
+    pub fn advance(self: &mut Self) -> bool {
+        let need_turnover = self.at_notch();
+        self.advance_by(1);
+        return need_turnover;
+    }
+
+    // advance the rotor several times without turnover of other rotors
+    pub fn advance_by(self: &mut Self, steps: usize) {
+        self.position = ((self.position as usize + steps % self.size) % self.size) as u8;
+    }
+
+    // index of input segment -> index of output segment
+    pub fn forward(self: &Self, input: u8) -> u8 {
+        return self.pass(&self.forward, input);
+    }
+
+    pub fn backward(self: &Self, input: u8) -> u8 {
+        return self.pass(&self.backward, input);
+    }
+
+    fn pass(self: &Self, wiring: &[u8; L], input: u8) -> u8 {
+        let size = self.size;
+        let shift = (self.position as usize + size - self.ring_offset as usize) % size;
+        let output = wiring[(input as usize + shift) % size] as usize;
+        return ((output + size - shift) % size) as u8;
+    }
+
+    fn check(self: &Self, segment: u8) -> Result<u8, Error> {
+        if segment as usize >= self.size {
+            return Err(Error::InvalidIndex(segment));
+        }
+        return Ok(segment);
+    }
+}
+
+impl Rotor {
+    pub fn new(wiring: &str, notches: &str) -> Result<Self, Error> {
+        let mut rotor = Self::with_indexes(&parse_wiring(wiring)?, &[])?;
+        for byte in notches.bytes() {
+            rotor.notches[index(byte)? as usize] = true;
+        }
+        return Ok(rotor);
+    }
+
+    // historical rotor by name, like "I" or "beta"
+    pub fn model(name: &str) -> Result<Self, Error> {
+        for (model, wiring, notches) in ROTORS.iter() {
+            if model.eq_ignore_ascii_case(name) {
+                return Self::new(wiring, notches);
+            }
+        }
+        return Err(Error::UnknownModel);
+    }
+
+    // thin rotor of M4 (beta or gamma), it has no pawl
+    pub fn is_thin(self: &Self) -> bool {
+        return [RING_BETA, RING_GAMMA]
+            .iter()
+            .any(|wiring| parse_wiring(wiring) == Ok(self.forward));
+    }
+
+    // letter of current segment
+    pub fn position(self: &Self) -> u8 {
+        return letter(self.position);
+    }
+
+    pub fn set_position(self: &mut Self, position: u8) -> Result<(), Error> {
+        self.position = index(position)?;
+        return Ok(());
+    }
+
+    pub fn ring_offset(self: &Self) -> u8 {
+        return letter(self.ring_offset);
+    }
+
+    pub fn set_ring_offset(self: &mut Self, offset: u8) -> Result<(), Error> {
+        self.ring_offset = index(offset)?;
+        return Ok(());
+    }
+}
+
+impl<const L: usize> Wheel for Rotor<L> {
+    fn at_notch(self: &Self) -> bool {
+        return Self::at_notch(self);
+    }
+
+    fn at_start(self: &Self) -> bool {
+        return self.position == 0;
+    }
+
+    fn turn(self: &mut Self) {
+        self.advance();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rotor::Rotor;
+    use crate::Error;
+
+    #[test]
+    fn rotor() {
+        let mut rotor = Rotor::model("i").unwrap();
+        assert_eq!(rotor.forward(0), 4);
+        assert_eq!(rotor.backward(4), 0);
+
+        rotor.set_position(b'Q').unwrap();
+        assert!(rotor.at_notch());
+        assert!(rotor.advance());
+        assert_eq!(rotor.position(), b'R');
+        assert!(rotor.advance() == false);
+
+        rotor.set_ring_offset(b'B').unwrap();
+        rotor.set_position(b'A').unwrap();
+        // ring offset B moves wiring one segment: A -> K
+        assert_eq!(rotor.forward(0), 10);
+
+        assert!(Rotor::model("gamma").unwrap().is_thin());
+        assert!(rotor.is_thin() == false);
+
+        assert_eq!(Rotor::model("IX"), Err(Error::UnknownModel));
+        assert_eq!(
+            Rotor::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ", "1"),
+            Err(Error::InvalidLetter('1'))
+        );
+    }
+
+    #[test]
+    fn indexes() {
+        // alphabet of 4 letters in rotor for up to 8
+        let mut rotor = Rotor::<8>::with_indexes(&[1, 3, 0, 2], &[3]).unwrap();
+        assert_eq!(rotor.size(), 4);
+        assert_eq!(rotor.forward(0), 1);
+        assert_eq!(rotor.backward(1), 0);
+
+        rotor.set_segment(3).unwrap();
+        assert!(rotor.advance());
+        assert_eq!(rotor.segment(), 0);
+        rotor.advance_by(9);
+        assert_eq!(rotor.segment(), 1);
+
+        rotor.set_ring_segment(1).unwrap();
+        rotor.set_segment(1).unwrap();
+        assert_eq!(rotor.forward(2), 0);
+
+        assert_eq!(rotor.set_segment(4), Err(Error::InvalidIndex(4)));
+        assert_eq!(
+            Rotor::<8>::with_indexes(&[1, 3, 0, 2], &[4]),
+            Err(Error::InvalidIndex(4))
+        );
+        assert_eq!(
+            Rotor::<2>::with_indexes(&[1, 0, 2], &[]),
+            Err(Error::InvalidWiringSize(3))
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::Error;
+use core::fmt;
+use core::str::FromStr;
+
+// Rotor as seen by stepping
+pub trait Wheel {
+    // pawl of the rotor on the left falls into the notch
+    fn at_notch(self: &Self) -> bool;
+    // first segment, after full revolution
+    fn at_start(self: &Self) -> bool;
+    fn turn(self: &mut Self);
+}
+
+// How rotors turn each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stepping {
+    // pawls and notches, with double step of the middle rotor (Enigma)
+    Ratchet,
+    // rotor turns next one when passes its notch, without double step
+    Gear,
+    // rotor turns next one after full revolution, notches are ignored
+    Odometer,
+}
+
+impl Stepping {
+    pub fn name(self: &Self) -> &'static str {
+        match self {
+            Stepping::Ratchet => return "ratchet",
+            Stepping::Gear => return "gear",
+            Stepping::Odometer => return "odometer",
+        }
+    }
+
+    // Turns wheels (from left to right) on key press. Wheels without pawl
+    // never turn, others are moving wheels.
+    //
+    // Ratchet: all pawls move on key press. The right pawl always catches
+    // ratchet of its wheel, other pawls catch it only if they fall into the
+    // notch of the next moving wheel, and then push that wheel too. So wheel
+    // left from the right one turns twice in a row (double step).
+    // See https://en.wikipedia.org/wiki/Enigma_rotor_details
+    pub fn advance<W: Wheel>(self: &Self, wheels: &mut [W], pawls: &[bool]) {
+        let moving = (0..wheels.len()).rev().filter(|slot| pawls[*slot]);
+
+        match self {
+            Stepping::Ratchet => {
+                // notches are checked before any wheel is turned, so a wheel
+                // turns only after the pawl on its left checked its notch
+                let mut next: Option<(usize, bool)> = None;
+                for slot in moving {
+                    let turn = match next {
+                        None => true,
+                        Some((next, next_turn)) => {
+                            let push = wheels[next].at_notch();
+                            if push || next_turn {
+                                wheels[next].turn();
+                            }
+                            push
+                        }
+                    };
+                    next = Some((slot, turn));
+                }
+                if let Some((next, true)) = next {
+                    wheels[next].turn();
+                }
+            }
+            Stepping::Gear => {
+                for slot in moving {
+                    let carry = wheels[slot].at_notch();
+                    wheels[slot].turn();
+                    if carry == false {
+                        break;
+                    }
+                }
+            }
+            Stepping::Odometer => {
+                for slot in moving {
+                    wheels[slot].turn();
+                    if wheels[slot].at_start() == false {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Stepping {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Stepping {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        for stepping in [Stepping::Ratchet, Stepping::Gear, Stepping::Odometer] {
+            if stepping.name().eq_ignore_ascii_case(s) {
+                return Ok(stepping);
+            }
+        }
+        return Err(Error::UnknownStepping);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rotor::Rotor;
+    use crate::stepping::Stepping;
+    use crate::Error;
+
+    fn rotors(positions: &[u8; 3]) -> [Rotor; 3] {
+        let mut rotors = ["I", "II", "III"].map(|name| Rotor::model(name).unwrap());
+        for (rotor, position) in rotors.iter_mut().zip(positions) {
+            rotor.set_position(*position).unwrap();
+        }
+        return rotors;
+    }
+
+    fn positions(rotors: &[Rotor; 3]) -> [u8; 3] {
+        return rotors.map(|rotor| rotor.position());
+    }
+
+    #[test]
+    fn advance() {
+        let mut wheels = rotors(b"ADU");
+        for _ in 0..3 {
+            Stepping::Ratchet.advance(&mut wheels, &[true; 3]);
+        }
+        assert_eq!(&positions(&wheels), b"BFX");
+
+        let mut wheels = rotors(b"ADU");
+        for _ in 0..3 {
+            Stepping::Gear.advance(&mut wheels, &[true; 3]);
+        }
+        assert_eq!(&positions(&wheels), b"AEX");
+
+        let mut wheels = rotors(b"AZZ");
+        Stepping::Odometer.advance(&mut wheels, &[true; 3]);
+        assert_eq!(&positions(&wheels), b"BAA");
+
+        // rotor without pawl never turns
+        let mut wheels = rotors(b"ADU");
+        for _ in 0..3 {
+            Stepping::Ratchet.advance(&mut wheels, &[false, true, true]);
+        }
+        assert_eq!(&positions(&wheels), b"AEX");
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!("Gear".parse::<Stepping>(), Ok(Stepping::Gear));
+        assert_eq!("ratchet".parse::<Stepping>(), Ok(Stepping::Ratchet));
+        assert_eq!("cog".parse::<Stepping>(), Err(Error::UnknownStepping));
+    }
+}
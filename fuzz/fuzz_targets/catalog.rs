@@ -0,0 +1,26 @@
+#![no_main]
+
+use enigma::device::catalog::Catalog;
+use libfuzzer_sys::fuzz_target;
+
+// catalog files, every loaded entry must give valid rotor or reflector
+fuzz_target!(|content: &str| {
+    for json in [false, true] {
+        let mut catalog = Catalog::new();
+        let loaded = match json {
+            true => catalog.load_json(content),
+            false => catalog.load_toml(content),
+        };
+        if loaded.is_err() {
+            continue;
+        }
+
+        for name in catalog.rotors() {
+            catalog.rotor(name).unwrap();
+            catalog.rotor(&format!("{}'", name)).unwrap();
+        }
+        for name in catalog.reflectors() {
+            catalog.reflector(name).unwrap();
+        }
+    }
+});
@@ -0,0 +1,69 @@
+#![no_main]
+#![allow(clippy::needless_return)]
+
+use arbitrary::Arbitrary;
+use enigma::device::block::Stepping;
+use enigma::device::details::REFLECTORS;
+use enigma::device::details::ROTORS;
+use enigma::device::Device;
+use libfuzzer_sys::fuzz_target;
+
+// whole machine with built-in models, encryption must be an involution for
+// any settings and text
+#[derive(Arbitrary, Debug)]
+struct Machine {
+    reflector: u8,
+    rotors: Vec<(u8, bool)>,
+    stepping: u8,
+    segments: String,
+    ring_offsets: String,
+    cables: String,
+    text: String,
+    threads: u8,
+    seek: u16,
+}
+
+fn device(machine: &Machine) -> Device {
+    let mut device = Device::new();
+    let (reflector, _) = REFLECTORS[machine.reflector as usize % REFLECTORS.len()];
+    device.set_reflector_type(reflector).unwrap();
+    for (rotor, reversed) in machine.rotors.iter().take(5) {
+        let (name, _, _) = ROTORS[*rotor as usize % ROTORS.len()];
+        match reversed {
+            true => device.add_rotor_type(&format!("{}'", name)).unwrap(),
+            false => device.add_rotor_type(name).unwrap(),
+        }
+    }
+    device.set_stepping(match machine.stepping % 3 {
+        0 => Stepping::Ratchet,
+        1 => Stepping::Gear,
+        _ => Stepping::Odometer,
+    });
+    _ = device.set_segments(&machine.segments);
+    _ = device.set_ring_offsets(&machine.ring_offsets);
+    _ = device.set_cables(&machine.cables, None);
+    return device;
+}
+
+fuzz_target!(|machine: Machine| {
+    let mut encoder = device(&machine);
+    let mut decoder = device(&machine);
+    encoder.seek(u64::from(machine.seek));
+    decoder.seek(u64::from(machine.seek));
+
+    let mut parallel = encoder.clone();
+    let encoded = parallel.crypt_parallel(&machine.text, usize::from(machine.threads));
+
+    let mut output = String::new();
+    for ch in machine.text.chars() {
+        if let Ok(out) = encoder.crypt(ch) {
+            assert_eq!(
+                decoder.crypt(out).unwrap(),
+                encoder.alphabet().normalize(ch).unwrap()
+            );
+            output.push(out);
+        }
+    }
+    assert_eq!(output, encoded);
+    assert_eq!(parallel.segments(), encoder.segments());
+});
@@ -0,0 +1,49 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use enigma::device::alphabet::Alphabet;
+use enigma::device::Device;
+use libfuzzer_sys::fuzz_target;
+
+// settings strings of the command line, applied to a machine with three
+// rotors of the alphabet
+#[derive(Arbitrary, Debug)]
+struct Settings {
+    alphabet: String,
+    plug_pairs: String,
+    cables: String,
+    max_cables: Option<u8>,
+    uhr: Option<(String, u8)>,
+    segments: String,
+    ring_offsets: String,
+    stepping: String,
+}
+
+fuzz_target!(|settings: Settings| {
+    let alphabet = Alphabet::new(&settings.alphabet).unwrap_or_default();
+    let mut device = Device::with_alphabet(&alphabet);
+
+    let wiring: String = alphabet.letters().iter().rev().collect();
+    let reflector: String = alphabet.letters().iter().rev().collect();
+    _ = device.set_reflector_wiring(&reflector);
+    for _ in 0..3 {
+        _ = device.add_rotor_wiring(&wiring, &wiring);
+    }
+
+    _ = device.set_plug_pairs(&settings.plug_pairs);
+    _ = device.set_cables(&settings.cables, settings.max_cables.map(usize::from));
+    _ = device.replace_cables(&settings.cables, None);
+    if let Some((cables, dial)) = settings.uhr {
+        _ = device.set_uhr(&cables, usize::from(dial));
+    }
+    _ = device.set_segments(&settings.segments);
+    _ = device.set_ring_offsets(&settings.ring_offsets);
+    if let Ok(stepping) = settings.stepping.parse() {
+        device.set_stepping(stepping);
+    }
+
+    for ch in settings.plug_pairs.chars() {
+        _ = device.crypt(ch);
+        _ = device.remove_plug_pair(ch);
+    }
+});
@@ -0,0 +1,55 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use enigma::device::alphabet::Alphabet;
+use enigma::device::reflector::Reflector;
+use enigma::device::rotor::Rotor;
+use enigma::device::uhr::Uhr;
+use libfuzzer_sys::fuzz_target;
+
+// constructors of rotors, reflectors and Uhr box from user strings
+#[derive(Arbitrary, Debug)]
+struct Wirings {
+    alphabet: String,
+    rotor: String,
+    notches: String,
+    reflector: String,
+    model: String,
+    position: usize,
+    offset: char,
+    uhr: String,
+    dial: usize,
+}
+
+fuzz_target!(|wirings: Wirings| {
+    let alphabet = Alphabet::new(&wirings.alphabet).unwrap_or_default();
+
+    if let Ok(mut rotor) = Rotor::with_alphabet(&alphabet, &wirings.rotor, &wirings.notches) {
+        rotor.set_position(wirings.position);
+        _ = rotor.set_ring_offset(wirings.offset);
+        for rotor in [rotor.clone(), rotor.reversed()] {
+            for ch in alphabet.letters() {
+                let out = rotor.forward(*ch).unwrap();
+                assert_eq!(rotor.backward(out).unwrap(), *ch);
+            }
+            rotor.steps_to_notch();
+        }
+    }
+
+    if let Ok(reflector) = Reflector::with_alphabet(&alphabet, &wirings.reflector) {
+        for ch in alphabet.letters() {
+            let out = reflector.forward(*ch).unwrap();
+            assert_eq!(reflector.backward(out).unwrap(), *ch);
+        }
+    }
+
+    _ = Rotor::model(&wirings.model);
+    _ = Reflector::model(&wirings.model);
+
+    if let Ok(uhr) = Uhr::with_alphabet(&alphabet, &wirings.uhr, wirings.dial) {
+        for ch in alphabet.letters() {
+            let out = uhr.forward(*ch).unwrap();
+            assert_eq!(uhr.backward(out).unwrap(), *ch);
+        }
+    }
+});
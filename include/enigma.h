@@ -0,0 +1,52 @@
+/*
+ * C API of Enigma simulator.
+ *
+ * Device is used through opaque handle created by enigma_device_new() and
+ * released by enigma_device_free(). Every function returns status code, and
+ * message of the last error of the calling thread is available by
+ * enigma_last_error(). Strings are NUL-terminated UTF-8.
+ *
+ * Settings of enigma_device_configure() have key format:
+ * "enigma-i | B | II IV V | BUL | BLA | AV BS CG DL FU HZ IN KM OW RX"
+ */
+
+#ifndef ENIGMA_H
+#define ENIGMA_H
+
+/* Generated from src/ffi.rs by cbindgen, do not edit. Run `ENIGMA_UPDATE_HEADER=1 cargo test --test ffi` to update */
+
+#include <stddef.h>
+
+typedef enum EnigmaStatus {
+  ENIGMA_STATUS_OK = 0,
+  ENIGMA_STATUS_NULL_POINTER = 1,
+  ENIGMA_STATUS_INVALID_UTF8 = 2,
+  ENIGMA_STATUS_INVALID_SETTINGS = 3,
+  ENIGMA_STATUS_BUFFER_TOO_SMALL = 4,
+  ENIGMA_STATUS_PANIC = 5,
+} EnigmaStatus;
+
+typedef struct EnigmaDevice EnigmaDevice;
+
+struct EnigmaDevice *enigma_device_new(void);
+
+void enigma_device_free(struct EnigmaDevice *device);
+
+enum EnigmaStatus enigma_device_configure(struct EnigmaDevice *device, const char *settings);
+
+enum EnigmaStatus enigma_device_crypt(struct EnigmaDevice *device,
+                                      const char *input,
+                                      size_t length,
+                                      char *output,
+                                      size_t capacity,
+                                      size_t *written);
+
+enum EnigmaStatus enigma_device_positions(struct EnigmaDevice *device,
+                                          char *buffer,
+                                          size_t capacity);
+
+enum EnigmaStatus enigma_device_set_positions(struct EnigmaDevice *device, const char *positions);
+
+const char *enigma_last_error(void);
+
+#endif  /* ENIGMA_H */
@@ -0,0 +1,194 @@
+use crate::device::alphabet::Alphabet;
+use std::error;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+// Keyed letter cipher with state, like rotor machines: every letter steps the
+// machine, and start positions are the key of the message. Command line,
+// stream adapters and statistics work with any machine through it
+pub trait Cipher {
+    fn alphabet(&self) -> &Alphabet;
+
+    // encrypts one letter and steps the machine. Characters out of the
+    // alphabet are errors and don't step it
+    fn crypt(&mut self, ch: char) -> Result<char, Box<dyn error::Error>>;
+
+    // same as key press, but without encryption
+    fn step(&mut self);
+
+    // letters of the alphabet, one for each wheel from left to right
+    fn positions(&self) -> String;
+
+    fn set_positions(&mut self, positions: &str) -> Result<(), Box<dyn error::Error>>;
+
+    // back to positions of the last set_positions
+    fn reset(&mut self);
+
+    // which positions change while encrypting, stators never do
+    fn moving(&self) -> Vec<bool> {
+        return vec![true; self.positions().chars().count()];
+    }
+}
+
+// Encrypts letters of the text, other characters are skipped and returned
+// with their offsets (in characters). Whitespaces are not reported, they only
+// separate words and groups
+pub fn crypt_text<C: Cipher + ?Sized>(cipher: &mut C, text: &str) -> (String, Vec<(usize, char)>) {
+    let mut output = String::with_capacity(text.len());
+    let mut skipped = Vec::new();
+    for (offset, ch) in text.chars().enumerate() {
+        match cipher.crypt(ch) {
+            Ok(out) => output.push(out),
+            Err(_) => {
+                if ch.is_whitespace() == false {
+                    skipped.push((offset, ch));
+                }
+            }
+        }
+    }
+    return (output, skipped);
+}
+
+// Encrypts input line by line, characters out of the alphabet are skipped.
+// Output is flushed after every line, so it works with interactive input
+pub fn crypt_lines<C, R, W>(
+    cipher: &mut C,
+    mut input: R,
+    output: &mut W,
+    transliterate: bool,
+) -> io::Result<()>
+where
+    C: Cipher + ?Sized,
+    R: BufRead,
+    W: Write,
+{
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        if transliterate {
+            line = cipher.alphabet().transliterate(&line);
+        }
+
+        let (encrypted, _) = crypt_text(cipher, &line);
+        output.write_all(encrypted.as_bytes())?;
+        output.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cipher::crypt_lines;
+    use crate::cipher::crypt_text;
+    use crate::cipher::Cipher;
+    use crate::device::alphabet::Alphabet;
+    use crate::device::Device;
+    use crate::stats::attack_positions;
+    use std::error;
+
+    // reciprocal Beaufort cipher with running key, position is the offset in
+    // the key. Not a rotor machine, but tools work with it all the same
+    const KEY: &str = "EKMFLGDQVZNTOWYHXUSPAIBRCJ";
+
+    #[derive(Clone)]
+    struct Beaufort {
+        alphabet: Alphabet,
+        start: usize,
+        offset: usize,
+    }
+
+    impl Cipher for Beaufort {
+        fn alphabet(&self) -> &Alphabet {
+            return &self.alphabet;
+        }
+
+        fn crypt(&mut self, ch: char) -> Result<char, Box<dyn error::Error>> {
+            let index = self.alphabet.index(ch).ok_or("invalid character")?;
+            let size = self.alphabet.len();
+            let key = self
+                .alphabet
+                .index(KEY.chars().nth(self.offset).unwrap())
+                .unwrap();
+            let out = self.alphabet.letter((key + size - index) % size);
+            self.step();
+            return Ok(out);
+        }
+
+        fn step(&mut self) {
+            self.offset = (self.offset + 1) % self.alphabet.len();
+        }
+
+        fn positions(&self) -> String {
+            return self.alphabet.letter(self.offset).to_string();
+        }
+
+        fn set_positions(&mut self, positions: &str) -> Result<(), Box<dyn error::Error>> {
+            let mut chars = positions.chars();
+            match (
+                chars.next().and_then(|ch| self.alphabet.index(ch)),
+                chars.next(),
+            ) {
+                (Some(offset), None) => self.start = offset,
+                _ => return Err(format!("invalid positions: {}", positions).into()),
+            }
+            self.reset();
+            return Ok(());
+        }
+
+        fn reset(&mut self) {
+            self.offset = self.start;
+        }
+    }
+
+    #[test]
+    fn other_machine() {
+        let mut cipher = Beaufort {
+            alphabet: Alphabet::latin(),
+            start: 0,
+            offset: 0,
+        };
+        cipher.set_positions("C").unwrap();
+        let (output, skipped) = crypt_text(&mut cipher, "AAA, A");
+        assert_eq!(output, "MFLG");
+        assert_eq!(skipped, [(3, ',')]);
+        assert_eq!(cipher.positions(), "G");
+
+        cipher.reset();
+        let mut output = Vec::new();
+        crypt_lines(&mut cipher, "AA\nÄ\n".as_bytes(), &mut output, true).unwrap();
+        assert_eq!(output, b"MFLC");
+
+        let plaintext = "FEINDLIQEINFANTERIEKOLONNEBEOBAQTET";
+        cipher.set_positions("Q").unwrap();
+        let (ciphertext, _) = crypt_text(&mut cipher, plaintext);
+        cipher.set_positions("A").unwrap();
+        let results = attack_positions(&cipher, &ciphertext, 1).unwrap();
+        assert_eq!(results[0].0, "Q");
+    }
+
+    #[test]
+    fn device() {
+        let mut device = Device::new();
+        device.set_reflector_type("B").unwrap();
+        for rotor in ["I", "II", "III"] {
+            device.add_rotor_type(rotor).unwrap();
+        }
+
+        let cipher: &mut dyn Cipher = &mut device;
+        cipher.reset();
+        assert_eq!(cipher.positions(), "AAA");
+        cipher.set_positions("ADU").unwrap();
+        let (output, _) = crypt_text(cipher, "HELLO");
+        cipher.step();
+        assert_eq!(cipher.positions(), "BFA");
+
+        cipher.reset();
+        assert_eq!(cipher.positions(), "ADU");
+        let (input, _) = crypt_text(cipher, &output);
+        assert_eq!(input, "HELLO");
+        assert_eq!(cipher.moving(), [true, true, true]);
+    }
+}
@@ -1,31 +1,75 @@
+pub mod alphabet;
+pub mod batch;
 pub mod block;
+pub mod builder;
+pub mod catalog;
 pub mod details;
+pub mod key;
+pub mod period;
 pub mod plugboard;
 pub mod reflector;
 pub mod rotor;
+pub mod trace;
+pub mod uhr;
 
+use crate::cipher::Cipher;
+use crate::device::alphabet::Alphabet;
 use crate::device::block::Block;
-use crate::device::details::ALPHABET;
+use crate::device::block::Notation;
+use crate::device::block::Stepping;
+use crate::device::period::PeriodAnalysis;
 use crate::device::plugboard::PlugBoard;
 use crate::device::reflector::Reflector;
 use crate::device::rotor::Rotor;
+use crate::device::trace::Stage;
+use crate::device::trace::Trace;
+use crate::device::trace::TraceStep;
+use crate::device::uhr::Uhr;
 use std::error;
 use std::fmt;
+use std::thread;
 
+#[derive(Debug, Clone)]
 pub struct Device {
     board: PlugBoard,
     block: Block,
+    start: Snapshot, // positions for reset
+}
+
+// devices are same if they encrypt same way from current positions, positions
+// for reset are not part of the machine
+impl PartialEq for Device {
+    fn eq(&self, other: &Self) -> bool {
+        return self.board == other.board && self.block == other.block;
+    }
+}
+
+impl Eq for Device {}
+
+// moving state of a device: positions of all rotors. Cheaper than clone of
+// the whole device, so search can go back to the same positions many times
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Snapshot {
+    positions: Vec<usize>,
 }
 
 #[derive(Debug, Clone)]
 pub enum DeviceError {
     InvalidCharacter(char),
+    AlphabetMismatch(/*actual*/ String, /*expected*/ String),
+    NoUhr,
 }
 
 impl fmt::Display for DeviceError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             DeviceError::InvalidCharacter(ch) => write!(f, "invalid character: {}", ch),
+            DeviceError::AlphabetMismatch(actual, expected) => write!(
+                f,
+                "alphabet mismatch: {}/{} (actual/expected)",
+                actual, expected
+            ),
+            DeviceError::NoUhr => write!(f, "device doesn't use Uhr"),
         }
     }
 }
@@ -34,20 +78,53 @@ impl error::Error for DeviceError {}
 
 impl Device {
     pub fn new() -> Self {
+        return Self::with_alphabet(&Alphabet::latin());
+    }
+
+    // all rotors, reflector and plug pairs must use the alphabet
+    pub fn with_alphabet(alphabet: &Alphabet) -> Self {
         Self {
-            board: PlugBoard::new(),
+            board: PlugBoard::with_alphabet(alphabet),
             block: Block::new(),
+            start: Snapshot {
+                positions: Vec::new(),
+            },
         }
     }
 
+    pub fn alphabet(&self) -> &Alphabet {
+        return self.board.alphabet();
+    }
+
+    fn check_alphabet(&self, alphabet: &Alphabet) -> Result<(), DeviceError> {
+        if alphabet != self.alphabet() {
+            return Err(DeviceError::AlphabetMismatch(
+                alphabet.to_string(),
+                self.alphabet().to_string(),
+            ));
+        }
+        return Ok(());
+    }
+
     // always left
-    pub fn set_reflector(&mut self, reflector: Reflector) {
+    pub fn set_reflector(&mut self, reflector: Reflector) -> Result<(), DeviceError> {
+        self.check_alphabet(reflector.alphabet())?;
         self.block.set_reflector(reflector);
+        Ok(())
     }
 
     // from left to right
-    pub fn add_rotor(&mut self, rotor: Rotor) {
+    pub fn add_rotor(&mut self, rotor: Rotor) -> Result<(), DeviceError> {
+        self.check_alphabet(rotor.alphabet())?;
         self.block.add_rotor(rotor);
+        Ok(())
+    }
+
+    // replaces cables or Uhr of the device
+    pub fn set_plugboard(&mut self, board: PlugBoard) -> Result<(), DeviceError> {
+        self.check_alphabet(board.alphabet())?;
+        self.board = board;
+        Ok(())
     }
 
     // always left
@@ -55,36 +132,106 @@ impl Device {
         &mut self,
         reflector_type: &str,
     ) -> Result<(), Box<dyn error::Error>> {
-        self.block.set_reflector(Reflector::model(reflector_type)?);
+        self.set_reflector(Reflector::model(reflector_type)?)?;
         Ok(())
     }
 
     // from left to right
     pub fn add_rotor_type(&mut self, rotor_type: &str) -> Result<(), Box<dyn error::Error>> {
-        self.block.add_rotor(Rotor::model(rotor_type)?);
+        self.add_rotor(Rotor::model(rotor_type)?)?;
+        Ok(())
+    }
+
+    // always left, wiring in the device alphabet
+    pub fn set_reflector_wiring(&mut self, wiring: &str) -> Result<(), Box<dyn error::Error>> {
+        let reflector = Reflector::with_alphabet(self.alphabet(), wiring)?;
+        self.set_reflector(reflector)?;
         Ok(())
     }
 
+    // from left to right, wiring and notches in the device alphabet
+    pub fn add_rotor_wiring(
+        &mut self,
+        wiring: &str,
+        notches: &str,
+    ) -> Result<(), Box<dyn error::Error>> {
+        let rotor = Rotor::with_alphabet(self.alphabet(), wiring, notches)?;
+        self.add_rotor(rotor)?;
+        Ok(())
+    }
+
+    pub fn stepping(&self) -> Stepping {
+        return self.block.stepping();
+    }
+
+    pub fn set_stepping(&mut self, stepping: Stepping) {
+        self.block.set_stepping(stepping);
+    }
+
+    // cycles of rotor positions for current rotors and stepping
+    pub fn period_analysis(&self) -> Result<PeriodAnalysis, Box<dyn error::Error>> {
+        return Ok(period::analyze(&self.block)?);
+    }
+
+    // same as `steps` key presses, but without encryption
+    pub fn seek(&mut self, steps: u64) {
+        self.block.seek(steps);
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        return Snapshot {
+            positions: self.block.positions(),
+        };
+    }
+
+    // snapshot must be taken from device with same count of rotors. Positions
+    // for reset are not changed
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), Box<dyn error::Error>> {
+        self.block.set_positions(&snapshot.positions)?;
+        Ok(())
+    }
+
+    // back to positions of the last set_segments, or to first segments if
+    // they were not set for current rotors
+    pub fn reset(&mut self) {
+        if self.block.set_positions(&self.start.positions).is_err() {
+            let first = vec![0; self.block.rotors().len()];
+            self.block
+                .set_positions(&first)
+                .expect("same count of rotors");
+        }
+    }
+
     pub fn segments(self: &Self) -> String {
         return self.block.segments();
     }
 
+    pub fn format_segments(self: &Self, notation: Notation) -> String {
+        return self.block.format_segments(notation);
+    }
+
     pub fn set_segments(
         self: &mut Self,
         segments: &str,
     ) -> Result<String, Box<dyn std::error::Error>> {
-        return Ok(self.block.set_segments(segments)?);
+        let segments = self.block.set_segments(segments)?;
+        self.start = self.snapshot();
+        return Ok(segments);
     }
 
     pub fn ring_offsets(&self) -> String {
         return self.block.ring_offsets();
     }
 
+    pub fn format_ring_offsets(&self, notation: Notation) -> String {
+        return self.block.format_ring_offsets(notation);
+    }
+
     pub fn set_ring_offsets(
         &mut self,
         offsets: &str,
     ) -> Result<String, Box<dyn std::error::Error>> {
-        return Ok(self.block.set_ring_offsets(offsets)?);
+        return self.block.set_ring_offsets(offsets);
     }
 
     pub fn plugboard(self: &Self) -> &String {
@@ -99,27 +246,222 @@ impl Device {
         return Ok(self.board.add_pair(input, output)?);
     }
 
+    // cables in key sheet notation, like "AV BS CG DL"
+    pub fn cables(&self) -> String {
+        return self.board.cables();
+    }
+
+    // add cables in key sheet notation, with optional limit of cables
+    pub fn set_cables(
+        &mut self,
+        cables: &str,
+        max: Option<usize>,
+    ) -> Result<String, Box<dyn error::Error>> {
+        return Ok(self.board.set_cables(cables, max)?);
+    }
+
+    // replace all plug connections, Uhr box included. Old connections are kept
+    // if any of cables is invalid
+    pub fn replace_cables(
+        &mut self,
+        cables: &str,
+        max: Option<usize>,
+    ) -> Result<String, Box<dyn error::Error>> {
+        return Ok(self.board.replace_cables(cables, max)?);
+    }
+
+    // unplug cable connected to the letter
+    pub fn remove_plug_pair(&mut self, ch: char) -> Result<(char, char), Box<dyn error::Error>> {
+        return Ok(self.board.remove_pair(ch)?);
+    }
+
+    // move end of the cable from one letter to another
+    pub fn rewire_plug(
+        &mut self,
+        from: char,
+        to: char,
+    ) -> Result<(char, char), Box<dyn error::Error>> {
+        return Ok(self.board.rewire(from, to)?);
+    }
+
+    pub fn clear_plugboard(&mut self) {
+        self.board.clear();
+    }
+
+    // use Uhr box instead of plug pairs, cables in key sheet notation and dial
+    // setting 00 - 39
+    pub fn set_uhr(&mut self, cables: &str, dial: usize) -> Result<(), Box<dyn error::Error>> {
+        let uhr = Uhr::with_alphabet(self.alphabet(), cables, dial)?;
+        self.board.set_uhr(uhr)?;
+        Ok(())
+    }
+
+    // None if Uhr box is not used
+    pub fn uhr_dial(&self) -> Option<usize> {
+        return self.board.uhr().map(|uhr| uhr.dial());
+    }
+
+    pub fn set_uhr_dial(&mut self, dial: usize) -> Result<usize, Box<dyn error::Error>> {
+        match self.board.uhr_mut() {
+            Some(uhr) => return Ok(uhr.set_dial(dial)?),
+            None => return Err(DeviceError::NoUhr.into()),
+        }
+    }
+
     pub fn set_plug_pairs(&mut self, plug_pairs: &str) -> Result<&String, Box<dyn error::Error>> {
         return Ok(self.board.set_plug_pairs(plug_pairs)?);
     }
 
     pub fn crypt(self: &mut Self, ch: char) -> Result<char, Box<dyn error::Error>> {
-        let mut val = ch.to_ascii_uppercase();
-        if ALPHABET.contains(val) == false {
-            return Err(DeviceError::InvalidCharacter(ch).into());
-        }
+        let mut val = self
+            .alphabet()
+            .normalize(ch)
+            .ok_or(DeviceError::InvalidCharacter(ch))?;
 
         val = self.board.crypt(val)?;
         val = self.block.crypt(val)?;
-        val = self.board.crypt(val)?;
+        val = self.board.backward(val)?;
 
         return Ok(val);
     }
+
+    // same as crypt, but returns every stage of the signal path
+    pub fn trace(self: &mut Self, ch: char) -> Result<Trace, Box<dyn error::Error>> {
+        let input = self
+            .alphabet()
+            .normalize(ch)
+            .ok_or(DeviceError::InvalidCharacter(ch))?;
+
+        let mut steps = Vec::new();
+        let board = self.board.crypt(input)?;
+        steps.push(TraceStep {
+            stage: Stage::Plugboard,
+            input,
+            output: board,
+        });
+
+        steps.extend(self.block.trace(board)?);
+        let val = steps.last().map(|step| step.output).unwrap_or(board);
+
+        let output = self.board.backward(val)?;
+        steps.push(TraceStep {
+            stage: Stage::PlugboardBack,
+            input: val,
+            output,
+        });
+
+        return Ok(Trace {
+            input,
+            output,
+            positions: self.segments(),
+            steps,
+        });
+    }
+
+    // rotors from left to right, stators included
+    pub fn rotors(&self) -> &[Rotor] {
+        return self.block.rotors();
+    }
+
+    pub fn stators(&self) -> usize {
+        return self.block.stators();
+    }
+
+    pub fn pawls(&self) -> &[bool] {
+        return self.block.pawls();
+    }
+
+    // which slots have a pawl, see Block::set_pawls
+    pub fn set_pawls(&mut self, pawls: &[bool]) -> Result<(), Box<dyn error::Error>> {
+        self.block.set_pawls(pawls)?;
+        Ok(())
+    }
+
+    pub fn reflector(&self) -> Option<&Reflector> {
+        return self.block.reflector();
+    }
+
+    // encrypt input by chunks on several threads. Like in sequential crypt
+    // invalid characters are skipped and don't advance rotors, and after the
+    // call the device is in the same state as after sequential crypt
+    pub fn crypt_parallel(&mut self, input: &str, threads: usize) -> String {
+        let letters: Vec<char> = input
+            .chars()
+            .filter(|ch| self.alphabet().contains(*ch))
+            .collect();
+        let chunk_size = letters.len().div_ceil(threads.max(1)).max(1);
+
+        let output = thread::scope(|scope| {
+            let mut handles = Vec::new();
+            for (i, chunk) in letters.chunks(chunk_size).enumerate() {
+                let mut device = self.clone();
+                device.seek((i * chunk_size) as u64);
+
+                handles.push(scope.spawn(move || {
+                    let mut encoded = String::new();
+                    for ch in chunk {
+                        match device.crypt(*ch) {
+                            Ok(out) => encoded.push(out),
+                            Err(_) => (), // ignore
+                        }
+                    }
+                    encoded
+                }));
+            }
+
+            let mut output = String::new();
+            for handle in handles {
+                output.push_str(&handle.join().expect("crypt thread panicked"));
+            }
+            output
+        });
+
+        self.seek(letters.len() as u64);
+
+        return output;
+    }
+}
+
+impl Cipher for Device {
+    fn alphabet(&self) -> &Alphabet {
+        return Device::alphabet(self);
+    }
+
+    fn crypt(&mut self, ch: char) -> Result<char, Box<dyn error::Error>> {
+        return Device::crypt(self, ch);
+    }
+
+    fn step(&mut self) {
+        self.seek(1);
+    }
+
+    fn positions(&self) -> String {
+        return self.segments();
+    }
+
+    fn set_positions(&mut self, positions: &str) -> Result<(), Box<dyn error::Error>> {
+        self.set_segments(positions)?;
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        Device::reset(self);
+    }
+
+    fn moving(&self) -> Vec<bool> {
+        return self.pawls().to_vec();
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::device::alphabet::Alphabet;
+    use crate::device::details::SEGMENTS;
+    use crate::device::rotor::Rotor;
+    use crate::device::trace::Stage;
     use crate::device::Device;
+    use crate::device::Snapshot;
+    use proptest::prelude::*;
 
     #[test]
     fn crypt() {
@@ -144,4 +486,341 @@ mod tests {
 
         assert_eq!(encoded, "WHJDZGZLEN");
     }
+
+    #[test]
+    fn crypt_parallel() {
+        let mut control = Device::new();
+        control.set_reflector_type("B").unwrap();
+        control.add_rotor_type("VI").unwrap();
+        control.add_rotor_type("II").unwrap();
+        control.add_rotor_type("VIII").unwrap();
+        control.set_plug_pairs("HWKLAO").unwrap();
+        control.set_segments("QDM").unwrap();
+        control.set_ring_offsets("IUP").unwrap();
+
+        let mut device = control.clone();
+
+        let input = "The quick brown fox jumps over the lazy dog, 42 times! ".repeat(50);
+        let mut expected = String::new();
+        for ch in input.chars() {
+            match control.crypt(ch) {
+                Ok(out) => expected.push(out),
+                Err(_) => (), // ignore
+            }
+        }
+
+        for threads in [1, 3, 8] {
+            let mut fork = device.clone();
+            assert_eq!(fork.crypt_parallel(&input, threads), expected);
+            assert_eq!(fork.segments(), control.segments());
+        }
+
+        assert_eq!(device.crypt_parallel("", 4), "");
+        assert_eq!(device.segments(), "QDM");
+    }
+
+    #[test]
+    fn uhr() {
+        let mut device = Device::new();
+        device.set_reflector_type("B").unwrap();
+        device.add_rotor_type("I").unwrap();
+        device.add_rotor_type("II").unwrap();
+        device.add_rotor_type("III").unwrap();
+        device.set_segments("PDU").unwrap();
+        device.set_uhr_dial(1).unwrap_err();
+
+        let mut plugged = device.clone();
+        plugged.set_plug_pairs("ATBLDFGJHMNWOPQYRZVX").unwrap();
+
+        device.set_uhr("AT BL DF GJ HM NW OP QY RZ VX", 0).unwrap();
+        assert_eq!(device.uhr_dial(), Some(0));
+
+        // dial 00 is same as plug pairs
+        let mut control = plugged.clone();
+        let mut uhr = device.clone();
+        for ch in "UHRBOXATZERO".chars() {
+            assert_eq!(uhr.crypt(ch).unwrap(), control.crypt(ch).unwrap());
+        }
+
+        // other dial settings are not reciprocal in plugboard, but the whole
+        // machine still decrypts own output
+        for dial in [1, 17, 39] {
+            let mut uhr = device.clone();
+            uhr.set_uhr_dial(dial).unwrap();
+            let mut decoder = uhr.clone();
+
+            let mut control = plugged.clone();
+            let mut differs = false;
+            for ch in "UHRBOXATNONZERODIAL".chars() {
+                let out = uhr.crypt(ch).unwrap();
+                assert_ne!(out, ch);
+                assert_eq!(decoder.crypt(out).unwrap(), ch);
+                differs |= out != control.crypt(ch).unwrap();
+            }
+            assert!(differs);
+        }
+    }
+
+    #[test]
+    fn equality() {
+        let mut device = Device::new();
+        device.set_reflector_type("B").unwrap();
+        device.add_rotor_type("I").unwrap();
+        let mut other = device.clone();
+
+        // order of cables doesn't change the machine
+        device.set_cables("AB CD", None).unwrap();
+        other.set_cables("CD AB", None).unwrap();
+        assert_eq!(device, other);
+
+        // positions for reset are not compared
+        device.set_segments("D").unwrap();
+        other.set_segments("A").unwrap();
+        assert_ne!(device, other);
+        other.restore(&device.snapshot()).unwrap();
+        assert_eq!(device, other);
+        other.reset();
+        assert_eq!(other.segments(), "A");
+    }
+
+    #[test]
+    fn snapshot() {
+        let mut device = Device::new();
+        device.set_reflector_type("B").unwrap();
+        device.add_rotor_type("I").unwrap();
+        device.add_rotor_type("II").unwrap();
+        device.add_rotor_type("III").unwrap();
+        device.set_cables("AV BS", None).unwrap();
+        device.set_segments("ADU").unwrap();
+
+        let fork = device.clone();
+        assert_eq!(fork, device);
+
+        let snapshot = device.snapshot();
+        let encoded: String = "SNAPSHOT"
+            .chars()
+            .map(|ch| device.crypt(ch).unwrap())
+            .collect();
+        assert_ne!(fork, device);
+        assert_ne!(device.snapshot(), snapshot);
+
+        // same positions give same output
+        device.restore(&snapshot).unwrap();
+        assert_eq!(device, fork);
+        let again: String = "SNAPSHOT"
+            .chars()
+            .map(|ch| device.crypt(ch).unwrap())
+            .collect();
+        assert_eq!(again, encoded);
+
+        // snapshot of other machine
+        let mut other = Device::new();
+        other.add_rotor_type("I").unwrap();
+        assert!(other.restore(&snapshot).is_err());
+        assert_eq!(other.snapshot(), Snapshot { positions: vec![0] });
+
+        // different configuration with same positions is other machine
+        let mut rewired = fork.clone();
+        rewired.rewire_plug('S', 'X').unwrap();
+        assert_eq!(rewired.snapshot(), fork.snapshot());
+        assert_ne!(rewired, fork);
+        assert!(format!("{:?}", fork).contains("Rotor"));
+    }
+
+    #[test]
+    fn trace() {
+        let mut device = Device::new();
+        device.set_reflector_type("B").unwrap();
+        device.add_rotor_type("I").unwrap();
+        device.add_rotor_type("II").unwrap();
+        device.add_rotor_type("III").unwrap();
+        device.set_cables("AV", None).unwrap();
+        let mut control = device.clone();
+
+        let trace = device.trace('a').unwrap();
+        assert_eq!(trace.input, 'A');
+        assert_eq!(trace.output, control.crypt('A').unwrap());
+        assert_eq!(trace.positions, "AAB");
+        assert_eq!(trace.steps.len(), 9);
+        assert_eq!(trace.steps[0].output, 'V');
+        assert_eq!(trace.steps[1].stage, Stage::Rotor(3));
+        assert_eq!(trace.steps[4].stage, Stage::Reflector);
+        assert_eq!(trace.steps[8].stage, Stage::PlugboardBack);
+
+        // every stage gets output of the previous one
+        for pair in trace.steps.windows(2) {
+            assert_eq!(pair[0].output, pair[1].input);
+        }
+        assert!(trace
+            .to_string()
+            .starts_with(&format!("A -> {} at AAB", trace.output)));
+
+        for ch in "TRACEISSAMEASCRYPT".chars() {
+            assert_eq!(device.trace(ch).unwrap().output, control.crypt(ch).unwrap());
+        }
+    }
+
+    #[test]
+    fn edit_plugboard() {
+        let mut device = Device::new();
+        device.set_reflector_type("B").unwrap();
+        device.add_rotor_type("I").unwrap();
+        device.add_rotor_type("II").unwrap();
+        device.add_rotor_type("III").unwrap();
+
+        let mut control = device.clone();
+        control.set_cables("AV CG DL", None).unwrap();
+
+        device.set_cables("AV BS CG DZ", None).unwrap();
+        device.remove_plug_pair('B').unwrap();
+        device.rewire_plug('Z', 'L').unwrap();
+        assert_eq!(device.cables(), "AV CG DL");
+
+        device.replace_cables("AV CG DL EE", None).unwrap_err();
+        assert_eq!(device.cables(), "AV CG DL");
+        for ch in "PLUGBOARDEDITING".chars() {
+            assert_eq!(device.crypt(ch).unwrap(), control.crypt(ch).unwrap());
+        }
+
+        device.clear_plugboard();
+        assert_eq!(device.cables(), "");
+        device.set_uhr("AT BL DF GJ HM NW OP QY RZ VX", 5).unwrap();
+    }
+
+    #[test]
+    fn custom_alphabet() {
+        let alphabet = Alphabet::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789").unwrap();
+        let mut device = Device::with_alphabet(&alphabet);
+        device
+            .set_reflector_wiring("MNVUS0W6JIY5AB38Z9E2DCG4KQF7TOXLH1PR")
+            .unwrap();
+        device
+            .add_rotor_wiring("5YKF31IXOM2W8PAT4HV9NC7GQBSL6R0EDZJU", "Q7")
+            .unwrap();
+        device
+            .add_rotor_wiring("1Z6MIXAQVYEFO97KNP0B2CWR45GU3SDLTH8J", "0")
+            .unwrap();
+        device.set_plug_pairs("A1B2").unwrap();
+        device.set_segments("Z9").unwrap();
+        device.set_ring_offsets("c3").unwrap();
+
+        let mut decoder = device.clone();
+
+        let mut encoded = String::new();
+        for ch in "Meet at 1530, gate 7!".chars() {
+            match device.crypt(ch) {
+                Ok(out) => encoded.push(out),
+                Err(_) => (), // ignore
+            }
+        }
+        assert_eq!(encoded.chars().count(), 15);
+
+        let mut decoded = String::new();
+        for ch in encoded.chars() {
+            decoded.push(decoder.crypt(ch).unwrap());
+        }
+        assert_eq!(decoded, "MEETAT1530GATE7");
+    }
+
+    #[test]
+    fn cyrillic_alphabet() {
+        let alphabet = Alphabet::new("АБВГДЕ").unwrap();
+        let mut device = Device::with_alphabet(&alphabet);
+        device.set_reflector_wiring("ГДЕАБВ").unwrap();
+        device.add_rotor_wiring("ВАЕДГБ", "г").unwrap();
+        device.add_rotor_wiring("ДЕГАБВ", "").unwrap();
+        device.set_plug_pairs("аб").unwrap();
+        device.set_segments("вг").unwrap();
+        assert_eq!(device.segments(), "ВГ");
+
+        let mut decoder = device.clone();
+        for ch in "абвгде".chars() {
+            let out = device.crypt(ch).unwrap();
+            assert!(alphabet.contains(out));
+            assert_eq!(decoder.crypt(out).unwrap(), alphabet.normalize(ch).unwrap());
+        }
+
+        device.crypt('Ж').unwrap_err();
+        device.set_segments("AB").unwrap_err();
+    }
+
+    #[test]
+    fn alphabet_mismatch() {
+        let alphabet = Alphabet::new("ABCDEF").unwrap();
+        let mut device = Device::with_alphabet(&alphabet);
+        device.add_rotor_type("I").unwrap_err();
+        device.set_reflector_type("B").unwrap_err();
+        device.add_rotor(Rotor::model("I").unwrap()).unwrap_err();
+        device.add_rotor_wiring("BADCFE", "").unwrap();
+        device
+            .add_rotor_wiring("ABCDEFGHIJKLMNOPQRSTUVWXYZ", "")
+            .unwrap_err();
+    }
+
+    // rotor order (reversed rotors included), ring offsets, segments and
+    // cables of random machine
+    fn settings() -> impl Strategy<Value = (Vec<String>, String, String, String)> {
+        let letters: Vec<char> = SEGMENTS.chars().collect();
+        let rotor = (
+            proptest::sample::select(vec!["I", "II", "III", "IV", "V", "VI", "VII", "VIII"]),
+            any::<bool>(),
+        )
+            .prop_map(|(rotor, reversed)| match reversed {
+                true => format!("{}'", rotor),
+                false => rotor.to_string(),
+            });
+
+        return (
+            proptest::collection::vec(rotor, 3..5),
+            proptest::collection::vec(proptest::sample::select(letters.clone()), 4),
+            proptest::collection::vec(proptest::sample::select(letters.clone()), 4),
+            Just(letters).prop_shuffle(),
+            0_usize..14,
+        )
+            .prop_map(|(rotors, offsets, segments, shuffled, cables)| {
+                let count = rotors.len();
+                let cables = shuffled[..cables * 2]
+                    .chunks(2)
+                    .map(|pair| pair.iter().collect::<String>())
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                (
+                    rotors,
+                    offsets[..count].iter().collect(),
+                    segments[..count].iter().collect(),
+                    cables,
+                )
+            });
+    }
+
+    fn machine(settings: &(Vec<String>, String, String, String)) -> Device {
+        let (rotors, offsets, segments, cables) = settings;
+        let mut device = Device::new();
+        device.set_reflector_type("B").unwrap();
+        for rotor in rotors.iter() {
+            device.add_rotor_type(rotor).unwrap();
+        }
+        device.set_ring_offsets(offsets).unwrap();
+        device.set_segments(segments).unwrap();
+        device.set_cables(cables, None).unwrap();
+        return device;
+    }
+
+    proptest! {
+        #[test]
+        fn encryption_is_involution(
+            settings in settings(),
+            text in "[A-Z]{1,300}",
+        ) {
+            let mut encoder = machine(&settings);
+            let mut decoder = machine(&settings);
+
+            for ch in text.chars() {
+                let out = encoder.crypt(ch).unwrap();
+                prop_assert_ne!(out, ch);
+                prop_assert_eq!(decoder.crypt(out).unwrap(), ch);
+            }
+            prop_assert_eq!(encoder.segments(), decoder.segments());
+        }
+    }
 }
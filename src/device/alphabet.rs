@@ -0,0 +1,217 @@
+use crate::device::details::ALPHABET;
+use crate::device::details::MAX_LETTERS;
+use std::error;
+use std::fmt;
+
+// Ordered set of letters that machine works with. Index of a letter in the
+// alphabet is the segment index for rotors, so all components of one device
+// must use same alphabet. Lower case input is accepted when alphabet contains
+// only upper case variant of the letter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    letters: Vec<char>,
+}
+
+#[derive(Debug, Clone)]
+pub enum AlphabetError {
+    Empty,
+    Duplicate(char),
+    TooLong(/*actual*/ usize, /*max*/ usize),
+}
+
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlphabetError::Empty => write!(f, "alphabet can not be empty"),
+            AlphabetError::Duplicate(ch) => write!(f, "duplicate letter in alphabet: {}", ch),
+            AlphabetError::TooLong(actual, max) => write!(
+                f,
+                "too many letters in alphabet: {}/{} (actual/max)",
+                actual, max
+            ),
+        }
+    }
+}
+
+impl error::Error for AlphabetError {}
+
+impl Alphabet {
+    pub fn new(letters: &str) -> Result<Self, AlphabetError> {
+        let mut retval = Vec::<char>::new();
+        for ch in letters.chars() {
+            if retval.contains(&ch) {
+                return Err(AlphabetError::Duplicate(ch));
+            }
+            retval.push(ch);
+        }
+
+        if retval.is_empty() {
+            return Err(AlphabetError::Empty);
+        }
+        if retval.len() > MAX_LETTERS {
+            return Err(AlphabetError::TooLong(retval.len(), MAX_LETTERS));
+        }
+
+        Ok(Self { letters: retval })
+    }
+
+    // A-Z, alphabet of historical machines
+    pub fn latin() -> Self {
+        Self {
+            letters: ALPHABET.chars().collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        return self.letters.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.letters.is_empty();
+    }
+
+    pub fn letter(&self, index: usize) -> char {
+        return self.letters[index % self.letters.len()];
+    }
+
+    pub fn letters(&self) -> &[char] {
+        return &self.letters;
+    }
+
+    // return letter of the alphabet for the input character, if any
+    pub fn normalize(&self, ch: char) -> Option<char> {
+        if self.letters.contains(&ch) {
+            return Some(ch);
+        }
+
+        let mut upper = ch.to_uppercase();
+        match (upper.next(), upper.next()) {
+            (Some(val), None) if self.letters.contains(&val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn index(&self, ch: char) -> Option<usize> {
+        let val = self.normalize(ch)?;
+        return self.letters.iter().position(|letter| *letter == val);
+    }
+
+    pub fn contains(&self, ch: char) -> bool {
+        return self.normalize(ch).is_some();
+    }
+
+    // indexes of letters for parts of the core, every character of `s` must
+    // be in the alphabet
+    pub fn indexes(&self, s: &str) -> Vec<u8> {
+        return s
+            .chars()
+            .map(|ch| self.index(ch).expect("letter of alphabet") as u8)
+            .collect();
+    }
+
+    // normalize every character that is in the alphabet, other characters are
+    // kept as is, so validation can report them
+    pub fn normalize_str(&self, s: &str) -> String {
+        return s
+            .chars()
+            .map(|ch| self.normalize(ch).unwrap_or(ch))
+            .collect();
+    }
+
+    // replace characters that are not in the alphabet by their usual
+    // spelling: German umlauts by two letters (Ä -> AE, ß -> SS) and
+    // fullwidth forms by ASCII ones. Other characters are kept as is
+    pub fn transliterate(&self, s: &str) -> String {
+        let mut retval = String::new();
+        for ch in s.chars() {
+            if self.contains(ch) {
+                retval.push(ch);
+                continue;
+            }
+
+            match ch {
+                'Ä' | 'ä' => retval.push_str("AE"),
+                'Ö' | 'ö' => retval.push_str("OE"),
+                'Ü' | 'ü' => retval.push_str("UE"),
+                'ß' | 'ẞ' => retval.push_str("SS"),
+                // fullwidth forms of ASCII characters
+                '\u{FF01}'..='\u{FF5E}' => {
+                    retval.push(char::from_u32(ch as u32 - 0xFEE0).expect("ASCII character"))
+                }
+                '\u{3000}' => retval.push(' '),
+                _ => retval.push(ch),
+            }
+        }
+        return retval;
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Self::latin()
+    }
+}
+
+impl fmt::Display for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for ch in self.letters.iter() {
+            write!(f, "{}", ch)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device::alphabet::Alphabet;
+
+    #[test]
+    fn new_alphabet() {
+        assert_eq!(Alphabet::latin().len(), 26);
+        assert_eq!(Alphabet::new("ABC123").unwrap().len(), 6);
+        assert_eq!(Alphabet::new("АБВГ").unwrap().len(), 4);
+
+        Alphabet::new("").unwrap_err();
+        Alphabet::new("ABCA").unwrap_err();
+
+        let letters: String = (0..65)
+            .map(|index| char::from_u32(0x400 + index).unwrap())
+            .collect();
+        Alphabet::new(&letters[..128]).unwrap();
+        Alphabet::new(&letters).unwrap_err();
+    }
+
+    #[test]
+    fn normalize() {
+        let alphabet = Alphabet::new("АБВГ12").unwrap();
+        assert_eq!(alphabet.normalize('б'), Some('Б'));
+        assert_eq!(alphabet.normalize('1'), Some('1'));
+        assert_eq!(alphabet.normalize('A'), None);
+        assert_eq!(alphabet.index('г'), Some(3));
+        assert_eq!(alphabet.letter(4), '1');
+        assert_eq!(alphabet.normalize_str("аб3"), "АБ3");
+
+        // lower case letters are kept if alphabet contains them
+        let alphabet = Alphabet::new("abAB").unwrap();
+        assert_eq!(alphabet.index('a'), Some(0));
+        assert_eq!(alphabet.index('A'), Some(2));
+    }
+
+    #[test]
+    fn transliterate() {
+        let alphabet = Alphabet::latin();
+        assert_eq!(
+            alphabet.transliterate("Ärger über Öl, Straße"),
+            "AErger UEber OEl, StraSSe"
+        );
+        assert_eq!(alphabet.transliterate("ＡＢＣ　ｘｙｚ１"), "ABC xyz1");
+        assert_eq!(
+            alphabet.normalize_str(&alphabet.transliterate("ｐｄｕ")),
+            "PDU"
+        );
+
+        // letters of the alphabet are never replaced
+        let alphabet = Alphabet::new("ABCÄÖÜ").unwrap();
+        assert_eq!(alphabet.transliterate("äöüß"), "äöüSS");
+    }
+}
@@ -0,0 +1,302 @@
+use crate::cipher::crypt_text;
+use crate::device::Device;
+use crate::device::Snapshot;
+use serde::Deserialize;
+use std::error;
+use std::fmt;
+
+// One intercepted message. Start positions of the message are set directly by
+// `positions`, or by `indicator` - message key encrypted at daily start
+// positions (Grundstellung). Doubled indicator (message key encrypted twice,
+// used before May 1940) is checked for both halves being same.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Record {
+    pub id: String,
+    #[serde(default)]
+    pub indicator: Option<String>,
+    #[serde(default)]
+    pub positions: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Processed {
+    // start positions of the message
+    pub key: String,
+    pub text: String,
+    // count of characters not in the alphabet, except whitespaces
+    pub skipped: usize,
+}
+
+// result of one record, `id` is the line number if the record can not be
+// parsed
+#[derive(Debug)]
+pub struct Outcome {
+    pub id: String,
+    pub result: Result<Processed, Box<dyn error::Error>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum BatchError {
+    InvalidRecord(/*line*/ usize, String),
+    NoKey,
+    BothKeys,
+    InvalidIndicator(/*actual*/ usize, /*expected*/ usize),
+    IndicatorMismatch(/*first*/ String, /*second*/ String),
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BatchError::InvalidRecord(line, reason) => {
+                write!(f, "invalid record at line {}: {}", line, reason)
+            }
+            BatchError::NoKey => write!(f, "record has neither indicator nor positions"),
+            BatchError::BothKeys => write!(f, "record has both indicator and positions"),
+            BatchError::InvalidIndicator(actual, expected) => write!(
+                f,
+                "invalid indicator length: {}/{} (actual/expected, or doubled)",
+                actual, expected
+            ),
+            BatchError::IndicatorMismatch(first, second) => write!(
+                f,
+                "halves of doubled indicator mismatch: {} {}",
+                first, second
+            ),
+        }
+    }
+}
+
+impl error::Error for BatchError {}
+
+// Processes records with one configured device. Every record starts from the
+// positions the device had on creation of the batch.
+pub struct Batch {
+    device: Device,
+    grundstellung: Snapshot,
+    transliterate: bool,
+}
+
+impl Batch {
+    pub fn new(device: Device) -> Self {
+        let grundstellung = device.snapshot();
+        return Self {
+            device,
+            grundstellung,
+            transliterate: false,
+        };
+    }
+
+    // transliterate text, indicator and positions of records before
+    // processing (see Alphabet::transliterate). Ids are kept as is
+    pub fn set_transliterate(&mut self, transliterate: bool) {
+        self.transliterate = transliterate;
+    }
+
+    pub fn device(&self) -> &Device {
+        return &self.device;
+    }
+
+    // start positions of the message, from record settings
+    pub fn message_key(&mut self, record: &Record) -> Result<String, Box<dyn error::Error>> {
+        self.device.restore(&self.grundstellung)?;
+
+        let indicator = match (&record.indicator, &record.positions) {
+            (None, Some(positions)) => return Ok(positions.clone()),
+            (Some(indicator), None) => indicator,
+            (None, None) => return Err(BatchError::NoKey.into()),
+            (Some(_), Some(_)) => return Err(BatchError::BothKeys.into()),
+        };
+
+        let count = self.device.rotors().len();
+        let mut key = String::new();
+        for ch in indicator.chars() {
+            key.push(self.device.crypt(ch)?);
+        }
+
+        let length = key.chars().count();
+        if length == count {
+            return Ok(key);
+        }
+        if length == count * 2 {
+            let first: String = key.chars().take(count).collect();
+            let second: String = key.chars().skip(count).collect();
+            if first != second {
+                return Err(BatchError::IndicatorMismatch(first, second).into());
+            }
+            return Ok(first);
+        }
+        return Err(BatchError::InvalidIndicator(length, count).into());
+    }
+
+    pub fn process(&mut self, record: &Record) -> Result<Processed, Box<dyn error::Error>> {
+        let record = self.prepare(record);
+        let key = self.message_key(&record)?;
+        self.device.set_segments(&key)?;
+
+        let (text, skipped) = crypt_text(&mut self.device, &record.text);
+        return Ok(Processed {
+            key,
+            text,
+            skipped: skipped.len(),
+        });
+    }
+
+    fn prepare(self: &Self, record: &Record) -> Record {
+        if self.transliterate == false {
+            return record.clone();
+        }
+
+        let alphabet = self.device.alphabet();
+        return Record {
+            id: record.id.clone(),
+            indicator: record.indicator.as_ref().map(|s| alphabet.transliterate(s)),
+            positions: record.positions.as_ref().map(|s| alphabet.transliterate(s)),
+            text: alphabet.transliterate(&record.text),
+        };
+    }
+
+    // Records are JSON objects, one per line. Empty lines and lines started
+    // with `#` are skipped. Errors are returned per record, so one bad record
+    // doesn't stop others.
+    pub fn run(&mut self, input: &str) -> Vec<Outcome> {
+        let mut outcomes = Vec::new();
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let outcome = match serde_json::from_str::<Record>(line) {
+                Ok(record) => Outcome {
+                    result: self.process(&record),
+                    id: record.id,
+                },
+                Err(err) => Outcome {
+                    id: format!("line {}", index + 1),
+                    result: Err(BatchError::InvalidRecord(index + 1, err.to_string()).into()),
+                },
+            };
+            outcomes.push(outcome);
+        }
+
+        return outcomes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device::batch::Batch;
+    use crate::device::batch::Record;
+    use crate::device::builder::DeviceBuilder;
+    use crate::device::builder::Machine;
+
+    // Barbarossa, 7 July 1941: indicator "KCH" at Grundstellung "WXC" gives
+    // message key "BLA"
+    fn batch() -> Batch {
+        let device = DeviceBuilder::new(Machine::EnigmaI)
+            .reflector("B")
+            .walzenlage(&["II", "IV", "V"])
+            .ringstellung("BUL")
+            .grundstellung("WXC")
+            .steckers("AV BS CG DL FU HZ IN KM OW RX")
+            .build()
+            .unwrap();
+        return Batch::new(device);
+    }
+
+    #[test]
+    fn process() {
+        let mut batch = batch();
+
+        let record = Record {
+            id: "1840".to_string(),
+            indicator: Some("KCH".to_string()),
+            positions: None,
+            text: "EDPUD NRGYS ZRCXN UYTPO MRMBO".to_string(),
+        };
+        let processed = batch.process(&record).unwrap();
+        assert_eq!(processed.key, "BLA");
+        assert_eq!(processed.text, "AUFKLXABTEILUNGXVONXKURTI");
+        assert_eq!(processed.skipped, 0);
+
+        // same record again gives same result
+        assert_eq!(batch.process(&record).unwrap(), processed);
+
+        let record = Record {
+            id: "1840".to_string(),
+            indicator: None,
+            positions: Some("BLA".to_string()),
+            text: "EDPUD!".to_string(),
+        };
+        let processed = batch.process(&record).unwrap();
+        assert_eq!(processed.text, "AUFKL");
+        assert_eq!(processed.skipped, 1);
+    }
+
+    #[test]
+    fn run() {
+        let mut batch = batch();
+        let input = r#"
+            # daily key of 7 July 1941
+            {"id": "1", "indicator": "KCH", "text": "EDPUD"}
+            {"id": "2", "text": "EDPUD"}
+            not a record
+            {"id": "3", "indicator": "KC", "text": "EDPUD"}
+            {"id": "4", "positions": "BLA", "indicator": "KCH", "text": "EDPUD"}
+            {"id": "5", "positions": "BL1", "text": "EDPUD"}
+            {"id": "6", "positions": "BLA", "text": "EDPUD"}
+        "#;
+
+        let outcomes = batch.run(input);
+        assert_eq!(outcomes.len(), 7);
+        assert_eq!(outcomes[0].result.as_ref().unwrap().text, "AUFKL");
+        assert!(outcomes[1].result.is_err());
+        assert_eq!(outcomes[2].id, "line 5");
+        assert!(outcomes[2].result.is_err());
+        assert!(outcomes[3].result.is_err());
+        assert!(outcomes[4].result.is_err());
+        assert!(outcomes[5].result.is_err());
+        assert_eq!(outcomes[6].result.as_ref().unwrap().text, "AUFKL");
+    }
+
+    #[test]
+    fn transliterate() {
+        let mut batch = batch();
+        let input = r#"{"id": "Ä1", "positions": "ＢＬＡ", "text": "ＥＤＰＵＤ"}"#;
+
+        let outcomes = batch.run(input);
+        assert!(outcomes[0].result.is_err());
+
+        batch.set_transliterate(true);
+        let outcomes = batch.run(input);
+        assert_eq!(outcomes[0].id, "Ä1");
+        let processed = outcomes[0].result.as_ref().unwrap();
+        assert_eq!(processed.key, "BLA");
+        assert_eq!(processed.text, "AUFKL");
+        assert_eq!(processed.skipped, 0);
+    }
+
+    #[test]
+    fn doubled_indicator() {
+        let mut batch = batch();
+        let mut device = batch.device().clone();
+        let indicator: String = "BLABLA"
+            .chars()
+            .map(|ch| device.crypt(ch).unwrap())
+            .collect();
+
+        let mut record = Record {
+            id: "1".to_string(),
+            indicator: Some(indicator.clone()),
+            positions: None,
+            text: String::new(),
+        };
+        assert_eq!(batch.message_key(&record).unwrap(), "BLA");
+
+        let mut broken: Vec<char> = indicator.chars().collect();
+        broken[5] = if broken[5] == 'A' { 'B' } else { 'A' };
+        record.indicator = Some(broken.iter().collect());
+        assert!(batch.message_key(&record).is_err());
+    }
+}
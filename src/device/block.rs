@@ -1,17 +1,74 @@
 use crate::device::reflector::Reflector;
 use crate::device::rotor::Rotor;
+use crate::device::rotor::RotorError;
+use crate::device::trace::Stage;
+use crate::device::trace::TraceStep;
 use std::error;
 use std::fmt;
+use std::str::FromStr;
 
+// How rotors turn each other, shared with the core
+pub use enigma_core::stepping::Stepping;
+
+// How segments and ring offsets are shown: letters like "BUL", or numbers of
+// Army and Luftwaffe wheels like "02 21 12"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    Letters,
+    Numbers,
+}
+
+impl Notation {
+    // letter of the rotor as it is shown on the wheel
+    pub fn format(self: &Self, rotor: &Rotor, letter: char) -> String {
+        match self {
+            Notation::Letters => return letter.to_string(),
+            Notation::Numbers => {
+                let index = rotor.alphabet().index(letter).expect("letter of rotor");
+                return format!("{:02}", index + 1);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Notation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Notation::Letters => write!(f, "letters"),
+            Notation::Numbers => write!(f, "numbers"),
+        }
+    }
+}
+
+impl FromStr for Notation {
+    type Err = BlockError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "letters" => Ok(Notation::Letters),
+            "numbers" => Ok(Notation::Numbers),
+            _ => Err(BlockError::InvalidNotation(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Block {
     reflector: Option<Reflector>,
     rotors: Vec<Rotor>,
+    pawls: Vec<bool>, // for every slot, rotor without pawl is a stator
+    stepping: Stepping,
 }
 
 #[derive(Debug, Clone)]
 pub enum BlockError {
     InvalidCountOfSegments(/*actual*/ usize, /*expected*/ usize),
     InvalidInput(/*input*/ char),
+    InvalidStepping(/*stepping*/ String),
+    InvalidSetting(/*char index*/ usize, /*reason*/ RotorError),
+    InvalidNotation(/*notation*/ String),
+    InvalidNumber(/*rotor index*/ usize, /*number*/ String),
+    InvalidCountOfPawls(/*actual*/ usize, /*expected*/ usize),
 }
 
 impl fmt::Display for BlockError {
@@ -23,6 +80,19 @@ impl fmt::Display for BlockError {
                 actual, expected
             ),
             BlockError::InvalidInput(ch) => write!(f, "invalid input character: {}", ch),
+            BlockError::InvalidStepping(s) => write!(f, "invalid stepping: {}", s),
+            BlockError::InvalidSetting(index, reason) => {
+                write!(f, "invalid setting at {}: {}", index, reason)
+            }
+            BlockError::InvalidNotation(s) => write!(f, "invalid notation: {}", s),
+            BlockError::InvalidNumber(index, number) => {
+                write!(f, "invalid number at {}: {}", index, number)
+            }
+            BlockError::InvalidCountOfPawls(actual, expected) => write!(
+                f,
+                "invalid count of pawls: {}/{} (actual/expected)",
+                actual, expected
+            ),
         }
     }
 }
@@ -34,52 +104,203 @@ impl Block {
         Self {
             reflector: None,
             rotors: Vec::<Rotor>::new(),
+            pawls: Vec::new(),
+            stepping: Stepping::Ratchet,
         }
     }
 
     pub fn set_reflector(&mut self, reflector: Reflector) {
+        let thin = self.thin_left();
         self.reflector = Some(reflector);
+        self.update_thin_left(thin);
     }
 
+    // rotor with pawl, it is inserted left from stators on the right side.
+    // Thin rotor of M4 at the left slot gets no pawl, see thin_left
     pub fn add_rotor(&mut self, rotor: Rotor) {
-        self.rotors.push(rotor);
+        let thin = self.thin_left();
+        let right = &self.pawls[thin as usize..];
+        let right_stators = right.iter().rev().take_while(|pawl| **pawl == false);
+        let slot = self.rotors.len() - right_stators.count();
+        self.rotors.insert(slot, rotor);
+        self.pawls.insert(slot, true);
+        self.update_thin_left(thin);
+    }
+
+    // M4 layout: thin rotor at the left slot, or four rotors with thin
+    // reflector. The left slot of M4 has no pawl, so its rotor never turns
+    fn thin_left(self: &Self) -> bool {
+        let Some(left) = self.rotors.first() else {
+            return false;
+        };
+        let thin_reflector = self
+            .reflector
+            .as_ref()
+            .is_some_and(|reflector| reflector.is_thin());
+        return left.is_thin() || (thin_reflector && self.rotors.len() == 4);
+    }
+
+    // pawl of the left slot follows the M4 layout, when the layout is changed
+    // by added rotor or reflector. set_pawls can change it after all
+    fn update_thin_left(self: &mut Self, before: bool) {
+        let after = self.thin_left();
+        if after != before {
+            self.pawls[0] = after == false;
+        }
+    }
+
+    // stator is a rotor that can be set to any position, but never turns.
+    // Stators are added from left to right after moving rotors
+    pub fn add_stator(&mut self, stator: Rotor) {
+        self.rotors.push(stator);
+        self.pawls.push(false);
+    }
+
+    // count of rotors without pawl, at any slots
+    pub fn stators(&self) -> usize {
+        return self.pawls.iter().filter(|pawl| **pawl == false).count();
+    }
+
+    // which slots have a pawl, from left to right
+    pub fn pawls(&self) -> &[bool] {
+        return &self.pawls;
+    }
+
+    // Stepping layout of the machine, like M4 where the thin rotor at the left
+    // slot has no pawl. Every pawl rides on the notch ring of the next rotor
+    // with pawl on the right, rotors without pawl never turn
+    pub fn set_pawls(&mut self, pawls: &[bool]) -> Result<(), BlockError> {
+        if pawls.len() != self.rotors.len() {
+            return Err(BlockError::InvalidCountOfPawls(
+                pawls.len(),
+                self.rotors.len(),
+            ));
+        }
+        self.pawls = pawls.to_vec();
+        return Ok(());
+    }
+
+    pub fn stepping(&self) -> Stepping {
+        return self.stepping;
+    }
+
+    pub fn set_stepping(&mut self, stepping: Stepping) {
+        self.stepping = stepping;
     }
 
     pub fn advance(self: &mut Self) {
-        if self.rotors.is_empty() {
+        self.stepping.advance(&mut self.rotors, &self.pawls);
+    }
+
+    // same as calling advance `steps` times. Turns of every moving rotor are
+    // counted from turns of the rotor on its right, so cost doesn't depend on
+    // count of steps
+    pub fn seek(self: &mut Self, steps: u64) {
+        // moving rotors from right to left
+        let moving: Vec<usize> = (0..self.rotors.len())
+            .rev()
+            .filter(|slot| self.pawls[*slot])
+            .collect();
+        if steps == 0 || moving.is_empty() {
             return;
         }
 
-        if self.rotors.last().unwrap().at_notch() {
-            for rotor in self.rotors.iter_mut().rev() {
-                if rotor.advance() == false {
-                    break;
-                }
-            }
-        } else {
-            // check on double step
-            // see https://en.wikipedia.org/wiki/Enigma_rotor_details
-            // Normalized Enigma sequences
-            let mut iter = self.rotors.iter_mut().rev();
-            iter.next().unwrap().advance();
-            match iter.next() {
-                None => return,
-                Some(next) => {
-                    if next.at_notch() == false {
-                        // not a double step case
-                        return;
-                    }
-                    next.advance();
-                }
-            }
+        let rotors: Vec<&Rotor> = moving.iter().map(|slot| &self.rotors[*slot]).collect();
+        let turns = match self.stepping {
+            Stepping::Ratchet => match Ratchet::new(&rotors) {
+                Some(ratchet) => ratchet.turns(steps as u128),
+                None => return self.seek_cycle(steps, &moving),
+            },
+            Stepping::Gear => Self::carries(&rotors, steps, |rotor, turns| {
+                // every turn from a notch turns the next rotor
+                let size = rotor.alphabet().len() as u128;
+                let position = rotor.position() as u128;
+                let notches = rotor.notch_positions();
+                let passed = notches
+                    .iter()
+                    .map(|notch| (*notch as u128 + size - position) % size)
+                    .filter(|distance| *distance < turns % size)
+                    .count();
+                return turns / size * notches.len() as u128 + passed as u128;
+            }),
+            Stepping::Odometer => Self::carries(&rotors, steps, |rotor, turns| {
+                return (rotor.position() as u128 + turns) / rotor.alphabet().len() as u128;
+            }),
+        };
 
-            // double step case
-            for rotor in iter {
-                if rotor.advance() == false {
-                    break;
-                }
+        for (slot, turns) in moving.iter().zip(turns) {
+            let rotor = &mut self.rotors[*slot];
+            let size = rotor.alphabet().len() as u128;
+            rotor.advance_by((turns % size) as usize);
+        }
+    }
+
+    // turns of moving rotors from right to left, when the next rotor turns
+    // only by `carry` of the previous one: turnovers of the rotor in count of
+    // its turns from current position
+    fn carries(rotors: &[&Rotor], steps: u64, carry: impl Fn(&Rotor, u128) -> u128) -> Vec<u128> {
+        let mut turns = vec![steps as u128];
+        for rotor in rotors[..rotors.len() - 1].iter() {
+            let last = *turns.last().expect("right rotor");
+            turns.push(carry(rotor, last));
+        }
+        return turns;
+    }
+
+    // Ratchet with notches at neighbour segments: push and double step of a
+    // rotor can fall on same press, so presses are counted one by one. After
+    // count of all states the block is on its cycle, so only the rest of the
+    // steps modulo length of the cycle is needed
+    fn seek_cycle(self: &mut Self, steps: u64, moving: &[usize]) {
+        let states = moving.iter().fold(1_u64, |states, slot| {
+            states.saturating_mul(self.rotors[*slot].alphabet().len() as u64)
+        });
+        if steps <= states {
+            for _ in 0..steps {
+                self.advance();
             }
+            return;
+        }
+
+        for _ in 0..states {
+            self.advance();
+        }
+        let cycle = self.positions();
+        let mut length = 1;
+        self.advance();
+        while self.positions() != cycle {
+            self.advance();
+            length += 1;
+        }
+
+        for _ in 0..((steps - states) % length) {
+            self.advance();
+        }
+    }
+
+    // indexes of current segments, from left to right
+    pub fn positions(self: &Self) -> Vec<usize> {
+        return self.rotors.iter().map(|rotor| rotor.position()).collect();
+    }
+
+    pub fn set_positions(self: &mut Self, positions: &[usize]) -> Result<(), BlockError> {
+        if positions.len() != self.rotors.len() {
+            return Err(BlockError::InvalidCountOfSegments(
+                positions.len(),
+                self.rotors.len(),
+            ));
+        }
+
+        for (rotor, position) in self.rotors.iter_mut().zip(positions) {
+            rotor.set_position(*position);
         }
+
+        return Ok(());
+    }
+
+    // from left to right
+    pub fn rotors(&self) -> &[Rotor] {
+        return &self.rotors;
     }
 
     pub fn segments(self: &Self) -> String {
@@ -91,16 +312,22 @@ impl Block {
         return retval;
     }
 
+    pub fn format_segments(self: &Self, notation: Notation) -> String {
+        return self.format(notation, |rotor| rotor.segment());
+    }
+
+    // segments like "BHR", or numbers like "02 08 18"
     pub fn set_segments(self: &mut Self, segments: &str) -> Result<String, Box<dyn error::Error>> {
-        if segments.len() != self.rotors.len() {
-            return Err(
-                BlockError::InvalidCountOfSegments(segments.len(), self.rotors.len()).into(),
-            );
-        }
+        let segments = self.settings(segments)?;
 
-        for i in 0..segments.len() {
-            _ = self.rotors[i].set_segment(segments.chars().nth(i).unwrap())?;
+        // all segments are checked before any rotor is turned
+        let mut rotors = self.rotors.clone();
+        for (index, (rotor, segment)) in rotors.iter_mut().zip(segments).enumerate() {
+            rotor
+                .set_segment(segment)
+                .map_err(|err| BlockError::InvalidSetting(index, err))?;
         }
+        self.rotors = rotors;
 
         return Ok(self.segments());
     }
@@ -115,49 +342,275 @@ impl Block {
         return offsets;
     }
 
+    pub fn format_ring_offsets(&self, notation: Notation) -> String {
+        return self.format(notation, |rotor| rotor.ring_offset());
+    }
+
+    // offsets like "BUL", or numbers like "02 21 12"
     pub fn set_ring_offsets(&mut self, offsets: &str) -> Result<String, Box<dyn error::Error>> {
-        if offsets.len() != self.rotors.len() {
-            return Err(
-                BlockError::InvalidCountOfSegments(offsets.len(), self.rotors.len()).into(),
-            );
+        let offsets = self.settings(offsets)?;
+
+        let mut rotors = self.rotors.clone();
+        for (index, (rotor, offset)) in rotors.iter_mut().zip(offsets).enumerate() {
+            rotor
+                .set_ring_offset(offset)
+                .map_err(|err| BlockError::InvalidSetting(index, err))?;
         }
+        self.rotors = rotors;
 
-        for i in 0..self.rotors.len() {
-            let rotor = &mut self.rotors[i];
-            let offset = offsets.chars().nth(i).unwrap();
+        return Ok(self.ring_offsets());
+    }
 
-            _ = rotor.set_ring_offset(offset)?;
+    // letter for each rotor from letters or numbers. Numbers are used only if
+    // setting has digits and whitespaces, and digits are not letters of the
+    // rotors
+    fn settings(self: &Self, setting: &str) -> Result<Vec<char>, BlockError> {
+        let numeric = setting.chars().any(|ch| ch.is_ascii_digit())
+            && setting
+                .chars()
+                .all(|ch| ch.is_ascii_digit() || ch.is_whitespace())
+            && self.rotors.iter().all(|rotor| {
+                setting
+                    .chars()
+                    .all(|ch| ch.is_whitespace() || rotor.alphabet().contains(ch) == false)
+            });
+        if numeric == false {
+            let letters: Vec<char> = setting.chars().collect();
+            if letters.len() != self.rotors.len() {
+                return Err(BlockError::InvalidCountOfSegments(
+                    letters.len(),
+                    self.rotors.len(),
+                ));
+            }
+            return Ok(letters);
         }
 
-        return Ok(self.ring_offsets());
+        let numbers: Vec<&str> = setting.split_whitespace().collect();
+        if numbers.len() != self.rotors.len() {
+            return Err(BlockError::InvalidCountOfSegments(
+                numbers.len(),
+                self.rotors.len(),
+            ));
+        }
+
+        let mut letters = Vec::new();
+        for (index, (rotor, number)) in self.rotors.iter().zip(numbers).enumerate() {
+            // wheels are numbered from 01
+            let alphabet = rotor.alphabet();
+            match number.parse::<usize>() {
+                Ok(value) if value >= 1 && value <= alphabet.len() => {
+                    letters.push(alphabet.letter(value - 1))
+                }
+                _ => return Err(BlockError::InvalidNumber(index, number.to_string())),
+            }
+        }
+        return Ok(letters);
+    }
+
+    fn format(self: &Self, notation: Notation, setting: fn(&Rotor) -> char) -> String {
+        let separator = match notation {
+            Notation::Letters => "",
+            Notation::Numbers => " ",
+        };
+        return self
+            .rotors
+            .iter()
+            .map(|rotor| notation.format(rotor, setting(rotor)))
+            .collect::<Vec<String>>()
+            .join(separator);
     }
 
     pub fn crypt(self: &mut Self, ch: char) -> Result<char, Box<dyn error::Error>> {
         self.advance();
+        return self.pass(ch, |_| ());
+    }
+
+    // same as crypt, but returns every stage of the signal path
+    pub fn trace(self: &mut Self, ch: char) -> Result<Vec<TraceStep>, Box<dyn error::Error>> {
+        self.advance();
+        let mut steps = Vec::new();
+        self.pass(ch, |step| steps.push(step))?;
+        return Ok(steps);
+    }
 
+    // signal path through rotors and reflector at current positions, every
+    // stage is reported to `visit`
+    fn pass(
+        self: &Self,
+        ch: char,
+        mut visit: impl FnMut(TraceStep),
+    ) -> Result<char, Box<dyn error::Error>> {
         let mut val = ch;
-        for rotor in self.rotors.iter().rev() {
-            val = rotor.forward(val)?;
+        for (slot, rotor) in self.rotors.iter().enumerate().rev() {
+            let output = rotor.forward(val)?;
+            visit(TraceStep {
+                stage: Stage::Rotor(slot + 1),
+                input: val,
+                output,
+            });
+            val = output;
         }
 
         match &self.reflector {
             None => return Ok(val),
-            Some(reflector) => val = reflector.forward(val)?,
+            Some(reflector) => {
+                let output = reflector.forward(val)?;
+                visit(TraceStep {
+                    stage: Stage::Reflector,
+                    input: val,
+                    output,
+                });
+                val = output;
+            }
         }
 
-        for rotor in self.rotors.iter() {
-            val = rotor.backward(val)?;
+        for (slot, rotor) in self.rotors.iter().enumerate() {
+            let output = rotor.backward(val)?;
+            visit(TraceStep {
+                stage: Stage::RotorBack(slot + 1),
+                input: val,
+                output,
+            });
+            val = output;
         }
 
         return Ok(val);
     }
+
+    pub fn reflector(&self) -> Option<&Reflector> {
+        return self.reflector.as_ref();
+    }
+}
+
+// Presses of ratchet stepping at which moving rotors are at notch, counted
+// without stepping. Right rotor turns on every press, so it gets to its
+// notches at fixed presses. Other rotor is pushed by its pawl on presses when
+// the rotor on its right is at notch, and if it stops at own notch, it turns
+// once more on the next press (double step) - that is the press when it is
+// at notch. The left rotor is never at notch for pawls. Rotors are levels
+// from right (0) to left
+struct Ratchet {
+    levels: Vec<Level>,
+}
+
+struct Level {
+    size: u128,
+    // distances from start position to notches, sorted
+    distances: Vec<u128>,
+    // rotor turns at own notch, so it is not the right or left one
+    double: bool,
+    // rotor starts at notch and the rotor on its right too, so first push
+    // is the same press as the double step
+    merged: bool,
+}
+
+impl Ratchet {
+    // None if any rotor, except the left one, has notches at neighbour
+    // segments, then rotor can be at notch on two presses in a row
+    fn new(rotors: &[&Rotor]) -> Option<Self> {
+        let mut levels = Vec::new();
+        for (index, rotor) in rotors.iter().enumerate() {
+            let size = rotor.alphabet().len() as u128;
+            let position = rotor.position() as u128;
+            let notches = rotor.notch_positions();
+            let neighbours = notches
+                .iter()
+                .any(|notch| notches.contains(&((notch + 1) % size as usize)));
+            if neighbours && index + 1 < rotors.len() {
+                return None;
+            }
+
+            let mut distances: Vec<u128> = notches
+                .iter()
+                .map(|notch| (*notch as u128 + size - position) % size)
+                .collect();
+            distances.sort();
+            levels.push(Level {
+                size,
+                distances,
+                double: index > 0 && index + 1 < rotors.len(),
+                merged: false,
+            });
+        }
+
+        let mut ratchet = Self { levels };
+        for index in 1..ratchet.levels.len() {
+            let merged = ratchet.at_start(index) && ratchet.press(index - 1, 0) == Some(0);
+            ratchet.levels[index].merged = merged;
+        }
+        return Some(ratchet);
+    }
+
+    // rotor is at notch before the first press, and turns on it by pawl
+    fn at_start(self: &Self, level: usize) -> bool {
+        let current = &self.levels[level];
+        return current.double && current.distances.first() == Some(&0);
+    }
+
+    // walk of the rotor from start position to its `index`-th notch
+    fn distance(self: &Self, level: usize, index: u128) -> u128 {
+        let current = &self.levels[level];
+        let count = current.distances.len() as u128;
+        return index / count * current.size + current.distances[(index % count) as usize];
+    }
+
+    // press at which the rotor is at notch for the `index`-th time, None if
+    // it never gets to notch
+    fn press(self: &Self, level: usize, index: u128) -> Option<u128> {
+        if self.levels[level].distances.is_empty() {
+            return None;
+        }
+        if level == 0 {
+            return Some(self.distance(0, index));
+        }
+        if index == 0 && self.at_start(level) {
+            return Some(0);
+        }
+
+        // every segment on the walk is left by push, except notches, that
+        // are left by double step
+        let push = self.distance(level, index) - index - 1 + self.levels[level].merged as u128;
+        return self.press(level - 1, push).map(|press| press + 1);
+    }
+
+    // count of presses before `presses` at which the rotor is at notch
+    fn count(self: &Self, level: usize, presses: u128) -> u128 {
+        // rotor is at notch at most once per press
+        let (mut low, mut high) = (0, presses);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.press(level, middle) {
+                Some(press) if press < presses => low = middle + 1,
+                _ => high = middle,
+            }
+        }
+        return low;
+    }
+
+    // turns of every rotor in `presses` key presses, from right to left
+    fn turns(self: &Self, presses: u128) -> Vec<u128> {
+        let mut turns = vec![presses];
+        for level in 1..self.levels.len() {
+            let mut count = self.count(level - 1, presses) - self.levels[level].merged as u128;
+            if self.levels[level].double {
+                count += self.count(level, presses);
+            }
+            turns.push(count);
+        }
+        return turns;
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::device::alphabet::Alphabet;
     use crate::device::block::Block;
+    use crate::device::block::Notation;
+    use crate::device::block::Stepping;
+    use crate::device::details::SEGMENTS;
     use crate::device::reflector::Reflector;
     use crate::device::rotor::Rotor;
+    use proptest::prelude::*;
 
     #[test]
     fn set_segments() {
@@ -171,6 +624,73 @@ mod tests {
         assert_eq!(block.segments(), "BHR");
     }
 
+    #[test]
+    fn numeric_settings() {
+        let mut block = Block::new();
+        block.set_reflector(Reflector::model("B").unwrap());
+        block.add_rotor(Rotor::model("II").unwrap());
+        block.add_rotor(Rotor::model("IV").unwrap());
+        block.add_rotor(Rotor::model("V").unwrap());
+
+        assert_eq!(block.set_ring_offsets("02 21 12").unwrap(), "BUL");
+        assert_eq!(block.set_segments(" 2  12 1 ").unwrap(), "BLA");
+        assert_eq!(block.format_segments(Notation::Numbers), "02 12 01");
+        assert_eq!(block.format_ring_offsets(Notation::Numbers), "02 21 12");
+        assert_eq!(block.format_ring_offsets(Notation::Letters), "BUL");
+        assert_eq!(Notation::Numbers.format(&block.rotors()[1], 'U'), "21");
+        assert_eq!(Notation::Letters.format(&block.rotors()[1], 'U'), "U");
+
+        let err = block.set_segments("02 27 01").unwrap_err();
+        assert_eq!(err.to_string(), "invalid number at 1: 27");
+        let err = block.set_segments("00 01 01").unwrap_err();
+        assert_eq!(err.to_string(), "invalid number at 0: 00");
+        let err = block.set_segments("02 12").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid count of segments: 2/3 (actual/expected)"
+        );
+        assert_eq!(block.segments(), "BLA");
+
+        // digits of the alphabet are letters
+        let alphabet = Alphabet::new("0123456789").unwrap();
+        let mut block = Block::new();
+        block.add_rotor(Rotor::with_alphabet(&alphabet, "1234567890", "0").unwrap());
+        block.add_rotor(Rotor::with_alphabet(&alphabet, "1234567890", "0").unwrap());
+        assert_eq!(block.set_segments("10").unwrap(), "10");
+    }
+
+    #[test]
+    fn multi_byte_settings() {
+        let mut block = Block::new();
+        block.add_rotor(Rotor::model("I").unwrap());
+        block.add_rotor(Rotor::model("II").unwrap());
+        block.set_segments("CD").unwrap();
+
+        // one character of two bytes is one segment
+        let err = block.set_segments("Ä").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid count of segments: 1/2 (actual/expected)"
+        );
+
+        // index of the offending character, not byte offset
+        let err = block.set_segments("ßÖ").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid setting at 0: invalid segment position: ß"
+        );
+        let err = block.set_ring_offsets("AＢ").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid setting at 1: invalid ring offset: Ｂ"
+        );
+
+        // rotors are not changed by invalid settings
+        block.set_segments("Aé").unwrap_err();
+        assert_eq!(block.segments(), "CD");
+        assert_eq!(block.ring_offsets(), "AA");
+    }
+
     #[test]
     fn check_segments_failure_too_much_segments() {
         let mut block = Block::new();
@@ -210,6 +730,300 @@ mod tests {
         assert_eq!(block.segments(), "BFY");
     }
 
+    #[test]
+    fn gear_and_odometer_stepping() {
+        let mut block = Block::new();
+        block.add_rotor(Rotor::model("I").unwrap());
+        block.add_rotor(Rotor::model("II").unwrap());
+        block.add_rotor(Rotor::model("III").unwrap());
+
+        // no double step
+        block.set_stepping(Stepping::Gear);
+        _ = block.set_segments("ADU").unwrap();
+        for _ in 0..4 {
+            block.advance();
+        }
+        assert_eq!(block.segments(), "AEY");
+
+        block.set_stepping(Stepping::Odometer);
+        _ = block.set_segments("AYY").unwrap();
+        for _ in 0..2 {
+            block.advance();
+        }
+        assert_eq!(block.segments(), "AZA");
+        _ = block.set_segments("AZZ").unwrap();
+        block.advance();
+        assert_eq!(block.segments(), "BAA");
+    }
+
+    #[test]
+    fn stators() {
+        let mut block = Block::new();
+        block.set_reflector(Reflector::model("B").unwrap());
+        block.add_rotor(Rotor::model("I").unwrap());
+        block.add_stator(Rotor::model("IV").unwrap());
+        block.add_stator(Rotor::model("V").unwrap());
+        block.add_rotor(Rotor::model("II").unwrap());
+        block.add_rotor(Rotor::model("III").unwrap());
+        assert_eq!(block.stators(), 2);
+
+        _ = block.set_segments("ADUJZ").unwrap();
+        for _ in 0..4 {
+            block.advance();
+        }
+        assert_eq!(block.segments(), "BFYJZ");
+
+        block.seek(16900);
+        assert_eq!(block.segments(), "BFYJZ");
+
+        // reciprocal, like any block with reflector
+        _ = block.set_segments("ADUJZ").unwrap();
+        let encoded = block.crypt('H').unwrap();
+        _ = block.set_segments("ADUJZ").unwrap();
+        assert_eq!(block.crypt(encoded).unwrap(), 'H');
+    }
+
+    fn layout(rotors: &[&str], pawls: &[bool], stepping: Stepping) -> Block {
+        let mut block = Block::new();
+        block.set_stepping(stepping);
+        for rotor in rotors {
+            block.add_rotor(Rotor::model(rotor).unwrap());
+        }
+        block.set_pawls(pawls).unwrap();
+        return block;
+    }
+
+    #[test]
+    fn pawls() {
+        // M4: thin rotor has no pawl, so left pawl doesn't push it
+        let mut block = layout(
+            &["BETA", "I", "II", "III"],
+            &[false, true, true, true],
+            Stepping::Ratchet,
+        );
+        assert_eq!(block.stators(), 1);
+        block.set_segments("AQEV").unwrap();
+        block.advance();
+        assert_eq!(block.segments(), "ARFW");
+
+        let mut block = layout(&["BETA", "I", "II", "III"], &[true; 4], Stepping::Ratchet);
+        block.set_segments("AQEV").unwrap();
+        block.advance();
+        assert_eq!(block.segments(), "BRFW");
+
+        // five rotors: every pawl falls into notch of the next rotor, rotor at
+        // notch turns even if the right rotor is not at notch
+        let mut block = layout(
+            &["I", "II", "III", "IV", "V"],
+            &[true; 5],
+            Stepping::Ratchet,
+        );
+        block.set_segments("QEAAA").unwrap();
+        block.advance();
+        assert_eq!(block.segments(), "RFAAB");
+        block.set_segments("QEVJZ").unwrap();
+        block.advance();
+        assert_eq!(block.segments(), "RFWKA");
+
+        // stator between moving rotors, left pawl rides on notch of the next
+        // moving rotor
+        let mut block = layout(
+            &["I", "IV", "II", "III"],
+            &[true, false, true, true],
+            Stepping::Ratchet,
+        );
+        block.set_segments("AJEV").unwrap();
+        block.advance();
+        assert_eq!(block.segments(), "BJFW");
+
+        block.set_stepping(Stepping::Odometer);
+        block.set_segments("AJZZ").unwrap();
+        block.advance();
+        assert_eq!(block.segments(), "BJAA");
+
+        // rotor is added left from stators on the right side
+        block.add_stator(Rotor::model("V").unwrap());
+        block.add_rotor(Rotor::model("VI").unwrap());
+        assert_eq!(block.pawls(), [true, false, true, true, true, false]);
+
+        let err = block.set_pawls(&[true, true]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid count of pawls: 2/6 (actual/expected)"
+        );
+    }
+
+    #[test]
+    fn thin_left() {
+        // thin rotor at the left slot has no pawl, other rotors are added
+        // right from it
+        let mut block = Block::new();
+        block.add_rotor(Rotor::model("BETA").unwrap());
+        for rotor in ["I", "II", "III"] {
+            block.add_rotor(Rotor::model(rotor).unwrap());
+        }
+        assert_eq!(block.pawls(), [false, true, true, true]);
+        block.set_segments("AQEV").unwrap();
+        block.advance();
+        assert_eq!(block.segments(), "ARFW");
+
+        // four rotors with thin reflector, in any order
+        let mut block = Block::new();
+        for rotor in ["I", "II", "III", "IV"] {
+            block.add_rotor(Rotor::model(rotor).unwrap());
+        }
+        assert_eq!(block.pawls(), [true; 4]);
+        block.set_reflector(Reflector::model("C-THIN").unwrap());
+        assert_eq!(block.pawls(), [false, true, true, true]);
+        block.add_rotor(Rotor::model("V").unwrap());
+        assert_eq!(block.pawls(), [true; 5]);
+
+        let mut block = Block::new();
+        block.set_reflector(Reflector::model("B-THIN").unwrap());
+        for rotor in ["I", "II", "III", "IV"] {
+            block.add_rotor(Rotor::model(rotor).unwrap());
+        }
+        assert_eq!(block.pawls(), [false, true, true, true]);
+        block.set_reflector(Reflector::model("B").unwrap());
+        assert_eq!(block.pawls(), [true; 4]);
+    }
+
+    #[test]
+    fn seek_with_pawls() {
+        let layouts: [&[bool]; 3] = [
+            &[false, true, true, true],
+            &[true, false, true, true],
+            &[true, true, false, true],
+        ];
+
+        for stepping in [Stepping::Ratchet, Stepping::Gear, Stepping::Odometer] {
+            for pawls in layouts {
+                for steps in [1, 25, 27, 700, 16900, 20000] {
+                    let rotors = ["VI", "I", "II", "III"];
+                    let mut control = layout(&rotors, pawls, stepping);
+                    let mut block = layout(&rotors, pawls, stepping);
+                    control.set_segments("BQEV").unwrap();
+                    block.set_segments("BQEV").unwrap();
+
+                    for _ in 0..steps {
+                        control.advance();
+                    }
+                    block.seek(steps);
+
+                    assert_eq!(block.segments(), control.segments());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn seek() {
+        let rotor_sets = [["I", "II", "III"], ["VI", "VII", "VIII"], ["II", "V", "VI"]];
+        let segments = ["ADU", "ZMQ", "KDO", "AEV"];
+
+        for stepping in [Stepping::Ratchet, Stepping::Gear, Stepping::Odometer] {
+            for rotor_set in rotor_sets.iter() {
+                for start in segments.iter() {
+                    for steps in [0, 1, 2, 25, 26, 27, 700, 16900, 20000] {
+                        let mut control = Block::new();
+                        let mut block = Block::new();
+                        control.set_stepping(stepping);
+                        block.set_stepping(stepping);
+                        for rotor in rotor_set.iter() {
+                            control.add_rotor(Rotor::model(rotor).unwrap());
+                            block.add_rotor(Rotor::model(rotor).unwrap());
+                        }
+                        control.set_segments(start).unwrap();
+                        block.set_segments(start).unwrap();
+
+                        for _ in 0..steps {
+                            control.advance();
+                        }
+                        block.seek(steps);
+
+                        assert_eq!(block.segments(), control.segments());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn seek_far() {
+        let mut control = Block::new();
+        let mut block = Block::new();
+        for rotor in ["III", "II", "I"] {
+            control.add_rotor(Rotor::model(rotor).unwrap());
+            block.add_rotor(Rotor::model(rotor).unwrap());
+        }
+
+        // period of three rotors with one notch each is 26 * 25 * 26
+        for _ in 0..(1_000_000_000_u64 % 16900) {
+            control.advance();
+        }
+        block.seek(1_000_000_000);
+
+        assert_eq!(block.segments(), control.segments());
+    }
+
+    #[test]
+    fn seek_five_rotors() {
+        let rotors = ["VI", "I", "VIII", "II", "III"];
+        for stepping in [Stepping::Ratchet, Stepping::Gear, Stepping::Odometer] {
+            for pawls in [[true; 5], [false, true, true, false, true]] {
+                let mut control = layout(&rotors, &pawls, stepping);
+                let mut block = layout(&rotors, &pawls, stepping);
+                control.set_segments("MQEZV").unwrap();
+                block.set_segments("MQEZV").unwrap();
+
+                for _ in 0..50_000 {
+                    control.advance();
+                }
+                block.seek(50_000);
+                assert_eq!(block.segments(), control.segments());
+
+                // far offsets are split at any point
+                let far = u64::MAX - 12345;
+                let mut whole = block.clone();
+                whole.seek(far);
+                block.seek(far - 1_000_000_007);
+                block.seek(1_000_000_007);
+                assert_eq!(block.segments(), whole.segments());
+            }
+        }
+
+        // same as with count of presses modulo cycle of the block
+        let rotors = ["VI", "I", "II", "III"];
+        let mut control = layout(&rotors, &[true; 4], Stepping::Ratchet);
+        let mut block = control.clone();
+        let moving = [3, 2, 1, 0];
+        control.seek_cycle(1 << 60, &moving);
+        block.seek(1 << 60);
+        assert_eq!(block.segments(), control.segments());
+    }
+
+    #[test]
+    fn seek_neighbour_notches() {
+        let mut control = Block::new();
+        for notches in ["", "EF", "AZ"] {
+            control.add_rotor(Rotor::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ", notches).unwrap());
+        }
+        control.set_segments("AYD").unwrap();
+        let mut block = control.clone();
+
+        for _ in 0..20000 {
+            control.advance();
+        }
+        block.seek(20000);
+        assert_eq!(block.segments(), control.segments());
+
+        let mut whole = block.clone();
+        whole.seek(1 << 40);
+        block.seek((1 << 40) - 20000);
+        block.seek(20000);
+        assert_eq!(block.segments(), whole.segments());
+    }
+
     #[test]
     fn crypt() {
         let mut block = Block::new();
@@ -230,4 +1044,63 @@ mod tests {
 
         assert_eq!(encoded, "MPVJAELATQ");
     }
+
+    proptest! {
+        #[test]
+        fn stepping_never_changes_reflector(
+            rotors in proptest::sample::subsequence(
+                vec!["I", "II", "III", "IV", "V", "VI", "VII", "VIII"],
+                1..5,
+            ),
+            reflector in proptest::sample::select(vec!["A", "B", "C"]),
+            stepping in proptest::sample::select(
+                vec![Stepping::Ratchet, Stepping::Gear, Stepping::Odometer],
+            ),
+            steps in 0_usize..2000,
+        ) {
+            let mut block = Block::new();
+            block.set_stepping(stepping);
+            block.set_reflector(Reflector::model(reflector).unwrap());
+            for rotor in rotors {
+                block.add_rotor(Rotor::model(rotor).unwrap());
+            }
+
+            let wiring = |block: &Block| -> String {
+                let reflector = block.reflector.as_ref().unwrap();
+                return SEGMENTS.chars().map(|ch| reflector.forward(ch).unwrap()).collect();
+            };
+
+            let before = wiring(&block);
+            for _ in 0..steps {
+                block.advance();
+            }
+            prop_assert_eq!(wiring(&block), before);
+        }
+
+        #[test]
+        fn seek_is_same_as_advance(
+            notches in proptest::collection::vec("[A-Z]{0,3}", 1..6),
+            moving in proptest::collection::vec(any::<bool>(), 5),
+            segments in "[A-Z]{5}",
+            stepping in proptest::sample::select(
+                vec![Stepping::Ratchet, Stepping::Gear, Stepping::Odometer],
+            ),
+            steps in 0_u64..3000,
+        ) {
+            let mut control = Block::new();
+            control.set_stepping(stepping);
+            for notches in notches.iter() {
+                control.add_rotor(Rotor::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ", notches).unwrap());
+            }
+            control.set_pawls(&moving[..notches.len()]).unwrap();
+            control.set_segments(&segments[..notches.len()]).unwrap();
+            let mut block = control.clone();
+
+            for _ in 0..steps {
+                control.advance();
+            }
+            block.seek(steps);
+            prop_assert_eq!(block.segments(), control.segments());
+        }
+    }
 }
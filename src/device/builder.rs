@@ -0,0 +1,480 @@
+use crate::device::catalog::Catalog;
+use crate::device::Device;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+// Historical machine types, each limits rotors, reflectors and cables that
+// can be used. Custom machine accepts anything from the catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Machine {
+    EnigmaI,
+    M3,
+    M4,
+    Custom,
+}
+
+const ENIGMA_I_ROTORS: [&str; 5] = ["I", "II", "III", "IV", "V"];
+const NAVAL_ROTORS: [&str; 8] = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII"];
+const THIN_ROTORS: [&str; 2] = ["BETA", "GAMMA"];
+const MAX_CABLES: usize = 13;
+
+impl Machine {
+    // count of rotors, thin rotor of M4 included
+    pub fn rotor_count(&self) -> Option<usize> {
+        match self {
+            Machine::EnigmaI | Machine::M3 => Some(3),
+            Machine::M4 => Some(4),
+            Machine::Custom => None,
+        }
+    }
+
+    // slots with pawl, thin rotor of M4 never turns
+    pub fn pawls(&self) -> Option<&'static [bool]> {
+        match self {
+            Machine::EnigmaI | Machine::M3 => Some(&[true, true, true]),
+            Machine::M4 => Some(&[false, true, true, true]),
+            Machine::Custom => None,
+        }
+    }
+
+    pub fn max_cables(&self) -> Option<usize> {
+        match self {
+            Machine::Custom => None,
+            _ => Some(MAX_CABLES),
+        }
+    }
+
+    pub fn reflectors(&self) -> Option<&'static [&'static str]> {
+        match self {
+            Machine::EnigmaI => Some(&["A", "B", "C"]),
+            Machine::M3 => Some(&["B", "C"]),
+            Machine::M4 => Some(&["B-THIN", "C-THIN"]),
+            Machine::Custom => None,
+        }
+    }
+
+    // rotors that fit the slot, from left to right
+    pub fn rotors(&self, slot: usize) -> Option<&'static [&'static str]> {
+        match self {
+            Machine::EnigmaI => Some(&ENIGMA_I_ROTORS),
+            Machine::M3 => Some(&NAVAL_ROTORS),
+            Machine::M4 if slot == 0 => Some(&THIN_ROTORS),
+            Machine::M4 => Some(&NAVAL_ROTORS),
+            Machine::Custom => None,
+        }
+    }
+}
+
+impl fmt::Display for Machine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Machine::EnigmaI => write!(f, "enigma-i"),
+            Machine::M3 => write!(f, "m3"),
+            Machine::M4 => write!(f, "m4"),
+            Machine::Custom => write!(f, "custom"),
+        }
+    }
+}
+
+impl FromStr for Machine {
+    type Err = BuildError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "enigma-i" | "i" => Ok(Machine::EnigmaI),
+            "m3" => Ok(Machine::M3),
+            "m4" => Ok(Machine::M4),
+            "custom" => Ok(Machine::Custom),
+            _ => Err(BuildError::single(
+                Setting::Machine,
+                format!("unknown machine: {}", s),
+            )),
+        }
+    }
+}
+
+// settings of the key sheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Machine,
+    Reflector,
+    Walzenlage,
+    Ringstellung,
+    Grundstellung,
+    Steckers,
+}
+
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Setting::Machine => write!(f, "machine"),
+            Setting::Reflector => write!(f, "reflector"),
+            Setting::Walzenlage => write!(f, "walzenlage"),
+            Setting::Ringstellung => write!(f, "ringstellung"),
+            Setting::Grundstellung => write!(f, "grundstellung"),
+            Setting::Steckers => write!(f, "steckers"),
+        }
+    }
+}
+
+// all problems of the configuration, not only the first one
+#[derive(Debug, Clone)]
+pub struct BuildError {
+    pub problems: Vec<(Setting, String)>,
+}
+
+impl BuildError {
+    pub fn single(setting: Setting, reason: String) -> Self {
+        Self {
+            problems: vec![(setting, reason)],
+        }
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid machine settings")?;
+        for (setting, reason) in self.problems.iter() {
+            write!(f, "\n  {}: {}", setting, reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for BuildError {}
+
+// Collects key sheet of the machine and creates ready Device in one call:
+//
+// let device = DeviceBuilder::new(Machine::EnigmaI)
+//     .reflector("B")
+//     .walzenlage(&["II", "IV", "V"])
+//     .ringstellung("BUL")
+//     .grundstellung("BLA")
+//     .steckers("AV BS CG DL FU HZ IN KM OW RX")
+//     .build()?;
+//
+// Empty ringstellung and grundstellung mean A for every rotor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceBuilder {
+    machine: Machine,
+    catalog: Catalog,
+    reflector: String,
+    walzenlage: Vec<String>,
+    ringstellung: String,
+    grundstellung: String,
+    steckers: String,
+}
+
+impl DeviceBuilder {
+    pub fn new(machine: Machine) -> Self {
+        Self {
+            machine,
+            catalog: Catalog::new(),
+            reflector: String::new(),
+            walzenlage: Vec::new(),
+            ringstellung: String::new(),
+            grundstellung: String::new(),
+            steckers: String::new(),
+        }
+    }
+
+    // rotors and reflectors are taken from the catalog, its alphabet is the
+    // alphabet of the device
+    pub fn catalog(mut self, catalog: &Catalog) -> Self {
+        self.catalog = catalog.clone();
+        return self;
+    }
+
+    pub fn reflector(mut self, reflector: &str) -> Self {
+        self.reflector = reflector.to_string();
+        return self;
+    }
+
+    // rotors from left to right, suffix ' means reversed rotor
+    pub fn walzenlage<S: AsRef<str>>(mut self, rotors: &[S]) -> Self {
+        self.walzenlage = rotors.iter().map(|s| s.as_ref().to_string()).collect();
+        return self;
+    }
+
+    pub fn ringstellung(mut self, offsets: &str) -> Self {
+        self.ringstellung = offsets.to_string();
+        return self;
+    }
+
+    pub fn grundstellung(mut self, segments: &str) -> Self {
+        self.grundstellung = segments.to_string();
+        return self;
+    }
+
+    // cables in key sheet notation, like "AV BS CG DL"
+    pub fn steckers(mut self, cables: &str) -> Self {
+        self.steckers = cables.to_string();
+        return self;
+    }
+
+    pub fn build(&self) -> Result<Device, BuildError> {
+        let mut problems = Vec::new();
+        let mut device = Device::with_alphabet(self.catalog.alphabet());
+
+        if let Err(reason) = self.set_reflector(&mut device) {
+            problems.push((Setting::Reflector, reason));
+        }
+
+        let rotors_added = match self.add_rotors(&mut device) {
+            Ok(()) => {
+                if let Some(pawls) = self.machine.pawls() {
+                    device.set_pawls(pawls).expect("count of rotors is checked");
+                }
+                true
+            }
+            Err(reasons) => {
+                for reason in reasons {
+                    problems.push((Setting::Walzenlage, reason));
+                }
+                false
+            }
+        };
+
+        // positions can be checked only against count of rotors, if some
+        // rotors are missed
+        for (setting, value) in [
+            (Setting::Ringstellung, &self.ringstellung),
+            (Setting::Grundstellung, &self.grundstellung),
+        ] {
+            if value.is_empty() {
+                continue;
+            }
+
+            let result = if rotors_added {
+                match setting {
+                    Setting::Ringstellung => device.set_ring_offsets(value),
+                    _ => device.set_segments(value),
+                }
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+            } else {
+                self.check_positions(value)
+            };
+
+            if let Err(reason) = result {
+                problems.push((setting, reason));
+            }
+        }
+
+        if let Err(err) = device.set_cables(&self.steckers, self.machine.max_cables()) {
+            problems.push((Setting::Steckers, err.to_string()));
+        }
+
+        if problems.is_empty() == false {
+            return Err(BuildError { problems });
+        }
+        return Ok(device);
+    }
+
+    fn set_reflector(&self, device: &mut Device) -> Result<(), String> {
+        if self.reflector.is_empty() {
+            return Err("reflector is not set".to_string());
+        }
+
+        let name = self.reflector.to_uppercase();
+        if let Some(reflectors) = self.machine.reflectors() {
+            if reflectors.contains(&name.as_str()) == false {
+                return Err(format!(
+                    "reflector {} is not used in {}, expected one of: {}",
+                    name,
+                    self.machine,
+                    reflectors.join(", ")
+                ));
+            }
+        }
+
+        let reflector = self
+            .catalog
+            .reflector(&name)
+            .map_err(|err| err.to_string())?;
+        return device
+            .set_reflector(reflector)
+            .map_err(|err| err.to_string());
+    }
+
+    fn add_rotors(&self, device: &mut Device) -> Result<(), Vec<String>> {
+        let mut reasons = Vec::new();
+
+        if let Some(count) = self.machine.rotor_count() {
+            if self.walzenlage.len() != count {
+                reasons.push(format!(
+                    "{} requires {} rotors, but have {}",
+                    self.machine,
+                    count,
+                    self.walzenlage.len()
+                ));
+            }
+        } else if self.walzenlage.is_empty() {
+            reasons.push("rotors are not set".to_string());
+        }
+
+        for (slot, rotor) in self.walzenlage.iter().enumerate() {
+            let name = rotor.to_uppercase();
+
+            if let Some(rotors) = self.machine.rotors(slot) {
+                if rotors.contains(&name.as_str()) == false {
+                    reasons.push(format!(
+                        "rotor {} can not be used at slot {} of {}, expected one of: {}",
+                        name,
+                        slot + 1,
+                        self.machine,
+                        rotors.join(", ")
+                    ));
+                    continue;
+                }
+                // every machine had one set of rotors
+                if self.walzenlage[..slot]
+                    .iter()
+                    .any(|other| other.to_uppercase() == name)
+                {
+                    reasons.push(format!("rotor {} is used twice", name));
+                    continue;
+                }
+            }
+
+            let result = self
+                .catalog
+                .rotor(&name)
+                .and_then(|rotor| Ok(device.add_rotor(rotor)?));
+            if let Err(err) = result {
+                reasons.push(err.to_string());
+            }
+        }
+
+        if reasons.is_empty() == false {
+            return Err(reasons);
+        }
+        return Ok(());
+    }
+
+    fn check_positions(&self, positions: &str) -> Result<(), String> {
+        let count = positions.chars().count();
+        if count != self.walzenlage.len() {
+            return Err(format!(
+                "invalid count of segments: {}/{} (actual/expected)",
+                count,
+                self.walzenlage.len()
+            ));
+        }
+
+        let alphabet = self.catalog.alphabet();
+        for (index, ch) in positions.chars().enumerate() {
+            if alphabet.contains(ch) == false {
+                return Err(format!(
+                    "invalid setting at {}: invalid character: {}",
+                    index, ch
+                ));
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device::alphabet::Alphabet;
+    use crate::device::builder::DeviceBuilder;
+    use crate::device::builder::Machine;
+    use crate::device::builder::Setting;
+    use crate::device::catalog::Catalog;
+
+    #[test]
+    fn build() {
+        let mut device = DeviceBuilder::new(Machine::EnigmaI)
+            .reflector("b")
+            .walzenlage(&["II", "IV", "V"])
+            .ringstellung("BUL")
+            .grundstellung("BLA")
+            .steckers("AV BS CG DL FU HZ IN KM OW RX")
+            .build()
+            .unwrap();
+
+        let plaintext: String = "EDPUDNRGYSZRCXNUYTPO"
+            .chars()
+            .map(|ch| device.crypt(ch).unwrap())
+            .collect();
+        assert_eq!(plaintext, "AUFKLXABTEILUNGXVONX");
+        assert_eq!(device.segments(), "BLU");
+    }
+
+    #[test]
+    fn m4() {
+        let device = DeviceBuilder::new(Machine::M4)
+            .reflector("B-thin")
+            .walzenlage(&["Beta", "II", "IV", "I"])
+            .ringstellung("AAAV")
+            .grundstellung("VJNA")
+            .build()
+            .unwrap();
+        assert_eq!(device.segments(), "VJNA");
+        assert_eq!(device.pawls(), [false, true, true, true]);
+
+        // thin rotor only at the left slot
+        let err = DeviceBuilder::new(Machine::M4)
+            .reflector("B-THIN")
+            .walzenlage(&["II", "BETA", "IV", "I"])
+            .build()
+            .unwrap_err();
+        assert_eq!(err.problems.len(), 2);
+    }
+
+    #[test]
+    fn aggregated_errors() {
+        let err = DeviceBuilder::new(Machine::EnigmaI)
+            .reflector("B-THIN")
+            .walzenlage(&["II", "VI", "II"])
+            .ringstellung("AB")
+            .grundstellung("AB1")
+            .steckers("AB CD AE")
+            .build()
+            .unwrap_err();
+
+        let settings: Vec<Setting> = err.problems.iter().map(|(setting, _)| *setting).collect();
+        assert_eq!(
+            settings,
+            [
+                Setting::Reflector,
+                Setting::Walzenlage,
+                Setting::Walzenlage,
+                Setting::Ringstellung,
+                Setting::Grundstellung,
+                Setting::Steckers,
+            ]
+        );
+        assert!(err
+            .to_string()
+            .starts_with("invalid machine settings\n  reflector:"));
+
+        // missed reflector and rotors
+        let err = DeviceBuilder::new(Machine::M3).build().unwrap_err();
+        assert_eq!(err.problems.len(), 2);
+    }
+
+    #[test]
+    fn custom() {
+        let mut catalog = Catalog::with_alphabet(&Alphabet::new("ABCD").unwrap());
+        catalog.add_rotor("R", "BCDA", "A").unwrap();
+        catalog.add_reflector("F", "BADC").unwrap();
+
+        let device = DeviceBuilder::new(Machine::Custom)
+            .catalog(&catalog)
+            .reflector("F")
+            .walzenlage(&["R", "R'", "R", "R"])
+            .grundstellung("DCBA")
+            .steckers("AC")
+            .build()
+            .unwrap();
+        assert_eq!(device.segments(), "DCBA");
+        assert_eq!(device.cables(), "AC");
+
+        assert_eq!("M4".parse::<Machine>().unwrap(), Machine::M4);
+        assert!("M5".parse::<Machine>().is_err());
+    }
+}
@@ -0,0 +1,359 @@
+use crate::device::alphabet::Alphabet;
+use crate::device::details::check_notches;
+use crate::device::details::check_reflector;
+use crate::device::details::check_ring;
+use crate::device::details::REFLECTORS;
+use crate::device::details::ROTORS;
+use crate::device::reflector::Reflector;
+use crate::device::reflector::ReflectorError;
+use crate::device::rotor::Rotor;
+use crate::device::rotor::RotorError;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+// Named rotor and reflector wirings. Catalog for A-Z alphabet starts with
+// built-in models and can be extended from TOML or JSON file like:
+//
+// [rotors.MYROTOR]
+// wiring = "EKMFLGDQVZNTOWYHXUSPAIBRCJ"
+// notches = "Q"
+//
+// [reflectors.MYREFLECTOR]
+// wiring = "YRUHQSLDPXNGOKMIEBFZCWVJAT"
+//
+// Names are case insensitive, entries from file replace built-in models with
+// same name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Catalog {
+    alphabet: Alphabet,
+    rotors: BTreeMap<String, RotorEntry>,
+    reflectors: BTreeMap<String, ReflectorEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RotorEntry {
+    pub wiring: String,
+    #[serde(default)]
+    pub notches: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ReflectorEntry {
+    pub wiring: String,
+}
+
+#[derive(Deserialize)]
+struct CatalogFile {
+    #[serde(default)]
+    rotors: BTreeMap<String, RotorEntry>,
+    #[serde(default)]
+    reflectors: BTreeMap<String, ReflectorEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub enum CatalogError {
+    InvalidEntry(/*name*/ String, /*reason*/ String),
+    InvalidFile(/*reason*/ String),
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CatalogError::InvalidEntry(name, reason) => {
+                write!(f, "invalid catalog entry {}: {}", name, reason)
+            }
+            CatalogError::InvalidFile(reason) => write!(f, "invalid catalog file: {}", reason),
+        }
+    }
+}
+
+impl error::Error for CatalogError {}
+
+impl Catalog {
+    pub fn new() -> Self {
+        return Self::with_alphabet(&Alphabet::latin());
+    }
+
+    // built-in models are available only for A-Z alphabet
+    pub fn with_alphabet(alphabet: &Alphabet) -> Self {
+        let mut catalog = Self {
+            alphabet: alphabet.clone(),
+            rotors: BTreeMap::new(),
+            reflectors: BTreeMap::new(),
+        };
+
+        if *alphabet == Alphabet::latin() {
+            for (name, wiring, notches) in ROTORS.iter() {
+                catalog
+                    .add_rotor(name, wiring, notches)
+                    .expect("valid built-in rotor");
+            }
+            for (name, wiring) in REFLECTORS.iter() {
+                catalog
+                    .add_reflector(name, wiring)
+                    .expect("valid built-in reflector");
+            }
+        }
+
+        return catalog;
+    }
+
+    pub fn alphabet(&self) -> &Alphabet {
+        return &self.alphabet;
+    }
+
+    pub fn add_rotor(
+        &mut self,
+        name: &str,
+        wiring: &str,
+        notches: &str,
+    ) -> Result<(), CatalogError> {
+        let name = Self::check_name(name)?;
+        let wiring = self.alphabet.normalize_str(wiring);
+        let notches = self.alphabet.normalize_str(notches);
+
+        check_ring(&self.alphabet, &wiring)
+            .map_err(|err| CatalogError::InvalidEntry(name.clone(), err.to_string()))?;
+        check_notches(&self.alphabet, &notches)
+            .map_err(|err| CatalogError::InvalidEntry(name.clone(), err.to_string()))?;
+
+        self.rotors.insert(name, RotorEntry { wiring, notches });
+        return Ok(());
+    }
+
+    pub fn add_reflector(&mut self, name: &str, wiring: &str) -> Result<(), CatalogError> {
+        let name = Self::check_name(name)?;
+        let wiring = self.alphabet.normalize_str(wiring);
+
+        check_reflector(&self.alphabet, &wiring)
+            .map_err(|err| CatalogError::InvalidEntry(name.clone(), err.to_string()))?;
+
+        self.reflectors.insert(name, ReflectorEntry { wiring });
+        return Ok(());
+    }
+
+    // `'` suffix is reserved for reversed rotors
+    fn check_name(name: &str) -> Result<String, CatalogError> {
+        let val = name.trim().to_uppercase();
+        if val.is_empty() || val.contains('\'') {
+            return Err(CatalogError::InvalidEntry(
+                name.to_string(),
+                "invalid name".to_string(),
+            ));
+        }
+        return Ok(val);
+    }
+
+    // names of rotors, in alphabetical order
+    pub fn rotors(&self) -> Vec<&String> {
+        return self.rotors.keys().collect();
+    }
+
+    pub fn reflectors(&self) -> Vec<&String> {
+        return self.reflectors.keys().collect();
+    }
+
+    // name with `'` suffix means reversed rotor, like "II'"
+    pub fn rotor(&self, name: &str) -> Result<Rotor, Box<dyn error::Error>> {
+        if let Some(model) = name.strip_suffix('\'') {
+            return Ok(self.rotor(model)?.reversed());
+        }
+
+        let val = name.to_uppercase();
+        let entry = self
+            .rotors
+            .get(&val)
+            .ok_or(RotorError::InvalidRotorType(val))?;
+
+        return Rotor::with_alphabet(&self.alphabet, &entry.wiring, &entry.notches);
+    }
+
+    pub fn reflector(&self, name: &str) -> Result<Reflector, Box<dyn error::Error>> {
+        let val = name.to_uppercase();
+        let entry = self
+            .reflectors
+            .get(&val)
+            .ok_or(ReflectorError::InvalidReflectorType(val))?;
+
+        return Reflector::with_alphabet(&self.alphabet, &entry.wiring);
+    }
+
+    // all entries are validated before any of them is added, so invalid file
+    // doesn't change the catalog
+    fn extend(&mut self, file: CatalogFile) -> Result<(), CatalogError> {
+        let mut catalog = self.clone();
+        for (name, entry) in file.rotors.iter() {
+            catalog.add_rotor(name, &entry.wiring, &entry.notches)?;
+        }
+        for (name, entry) in file.reflectors.iter() {
+            catalog.add_reflector(name, &entry.wiring)?;
+        }
+
+        *self = catalog;
+        return Ok(());
+    }
+
+    pub fn load_toml(&mut self, content: &str) -> Result<(), CatalogError> {
+        let file: CatalogFile =
+            toml::from_str(content).map_err(|err| CatalogError::InvalidFile(err.to_string()))?;
+        return self.extend(file);
+    }
+
+    pub fn load_json(&mut self, content: &str) -> Result<(), CatalogError> {
+        let file: CatalogFile = serde_json::from_str(content)
+            .map_err(|err| CatalogError::InvalidFile(err.to_string()))?;
+        return self.extend(file);
+    }
+
+    // format is chosen by extension: .json or .toml
+    pub fn load(&mut self, path: &Path) -> Result<(), Box<dyn error::Error>> {
+        let content = fs::read_to_string(path)?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => self.load_json(&content)?,
+            Some("toml") => self.load_toml(&content)?,
+            _ => {
+                return Err(CatalogError::InvalidFile(format!(
+                    "unknown format of {}, expected .toml or .json",
+                    path.display()
+                ))
+                .into())
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device::alphabet::Alphabet;
+    use crate::device::catalog::Catalog;
+    use crate::device::rotor::Rotor;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn built_in_models() {
+        let catalog = Catalog::new();
+        assert_eq!(catalog.rotors().len(), 10);
+        assert_eq!(catalog.reflectors().len(), 5);
+
+        let rotor = catalog.rotor("vi").unwrap();
+        let control = Rotor::model("VI").unwrap();
+        for ch in "ABCDEFGHIJKLMNOPQRSTUVWXYZ".chars() {
+            assert_eq!(rotor.forward(ch).unwrap(), control.forward(ch).unwrap());
+        }
+        assert!(catalog.rotor("II'").unwrap().is_reversed());
+        assert!(catalog.rotor("IX").is_err());
+        assert!(catalog.reflector("B").is_ok());
+        assert!(catalog.reflector("D").is_err());
+
+        assert!(Catalog::with_alphabet(&Alphabet::new("ABCD").unwrap())
+            .rotors()
+            .is_empty());
+    }
+
+    #[test]
+    fn load_toml() {
+        let mut catalog = Catalog::new();
+        catalog
+            .load_toml(
+                r#"
+                [rotors.MYROTOR]
+                wiring = "bdfhjlcprtxvznyeiwgakmusqo"
+                notches = "ah"
+
+                [rotors.noturn]
+                wiring = "EKMFLGDQVZNTOWYHXUSPAIBRCJ"
+
+                [reflectors.UKW-D]
+                wiring = "FOWULAQYSRTEZVBXGJIKDNCPHM"
+                "#,
+            )
+            .unwrap();
+
+        let mut rotor = catalog.rotor("myrotor").unwrap();
+        rotor.set_segment('H').unwrap();
+        assert!(rotor.at_notch());
+        assert_eq!(catalog.rotor("NOTURN").unwrap().steps_to_notch(), None);
+        assert!(catalog.reflector("ukw-d").is_ok());
+        assert_eq!(catalog.rotors().len(), 12);
+    }
+
+    #[test]
+    fn load_json() {
+        let mut catalog = Catalog::new();
+        catalog
+            .load_json(
+                r#"{
+                    "rotors": {"X1": {"wiring": "BDFHJLCPRTXVZNYEIWGAKMUSQO", "notches": "V"}},
+                    "reflectors": {}
+                }"#,
+            )
+            .unwrap();
+        assert!(catalog.rotor("X1").is_ok());
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let mut catalog = Catalog::new();
+
+        // missed segment
+        catalog
+            .load_toml("[rotors.X]\nwiring = \"BDFHJLCPRTXVZNYEIWGAKMUSQQ\"\n")
+            .unwrap_err();
+        // invalid notch
+        catalog
+            .load_toml("[rotors.X]\nwiring = \"BDFHJLCPRTXVZNYEIWGAKMUSQO\"\nnotches = \"1\"\n")
+            .unwrap_err();
+        // invalid size
+        catalog
+            .load_json(r#"{"reflectors": {"X": {"wiring": "ABC"}}}"#)
+            .unwrap_err();
+        // not reciprocal reflector
+        catalog
+            .load_json(r#"{"reflectors": {"X": {"wiring": "EKMFLGDQVZNTOWYHXUSPAIBRCJ"}}}"#)
+            .unwrap_err();
+        // reserved name
+        catalog
+            .load_json(r#"{"reflectors": {"X'": {"wiring": "YRUHQSLDPXNGOKMIEBFZCWVJAT"}}}"#)
+            .unwrap_err();
+        // syntax
+        catalog.load_toml("[rotors.X").unwrap_err();
+
+        // valid entries from invalid file are not added
+        catalog
+            .load_toml(
+                r#"
+                [rotors.A1]
+                wiring = "BDFHJLCPRTXVZNYEIWGAKMUSQO"
+                [rotors.A2]
+                wiring = "BDFHJLCPRTXVZNYEIWGAKMUSQ"
+                "#,
+            )
+            .unwrap_err();
+        assert!(catalog.rotor("A1").is_err());
+    }
+
+    #[test]
+    fn load_file() {
+        let path = env::temp_dir().join(format!("enigma-catalog-{}.toml", std::process::id()));
+        fs::write(
+            &path,
+            "[rotors.FILEROTOR]\nwiring = \"BDFHJLCPRTXVZNYEIWGAKMUSQO\"\nnotches = \"V\"\n",
+        )
+        .unwrap();
+
+        let mut catalog = Catalog::new();
+        catalog.load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(catalog.rotor("FILEROTOR").is_ok());
+        assert!(catalog.load(&path.with_extension("yaml")).is_err());
+    }
+}
@@ -1,37 +1,26 @@
+use crate::device::alphabet::Alphabet;
 use std::error;
 use std::fmt;
 
-pub const ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+// wirings of historical models are shared with the core without std
+pub use enigma_core::models::*;
+
 pub const SEGMENTS: &str = ALPHABET;
 
-// for rotors
-pub const RING_I: &str = "EKMFLGDQVZNTOWYHXUSPAIBRCJ";
-pub const RING_II: &str = "AJDKSIRUXBLHWTMCQGZNPYFVOE";
-pub const RING_III: &str = "BDFHJLCPRTXVZNYEIWGAKMUSQO";
-pub const RING_IV: &str = "ESOVPZJAYQUIRHXLNFTGKDCMWB";
-pub const RING_V: &str = "VZBRGITYUPSDNHLXAWMJQOFECK";
-pub const RING_VI: &str = "JPGVOUMFYQBENHZRDKASXLICTW";
-pub const RING_VII: &str = "NZJHGRCXMYSWBOUFAIVLPEKQDT";
-pub const RING_VIII: &str = "FKQHTLXOCBJSPDZRAMEWNIUYGV";
-// for reflectors
-pub const RING_A: &str = "EJMZALYXVBWFCRQUONTSPIKHGD";
-pub const RING_B: &str = "YRUHQSLDPXNGOKMIEBFZCWVJAT";
-pub const RING_C: &str = "FVPJIAOYEDRZXWGCTKUQSBNMHL";
+// Rotors, reflectors and plugboards keep indexes of their letters in parts of
+// the core, those have fixed capacity. So alphabet can't be longer
+pub const MAX_LETTERS: usize = 64;
 
-pub const NOTCH_I: &str = "Q";
-pub const NOTCH_II: &str = "E";
-pub const NOTCH_III: &str = "V";
-pub const NOTCH_IV: &str = "J";
-pub const NOTCH_V: &str = "Z";
-pub const NOTCH_VI: &str = "ZM";
-pub const NOTCH_VII: &str = "ZM";
-pub const NOTCH_VIII: &str = "ZM";
+pub type CoreRotor = enigma_core::rotor::Rotor<MAX_LETTERS>;
+pub type CoreReflector = enigma_core::reflector::Reflector<MAX_LETTERS>;
+pub type CorePlugBoard = enigma_core::plugboard::PlugBoard<MAX_LETTERS>;
 
 #[derive(Debug, Clone)]
 pub enum SegmentError {
     InvalidRingSize(/*actual*/ usize, /*expected*/ usize),
     MissedRingSegment(/*missed*/ char),
     InvalidNotch(/*notch*/ char),
+    NotReciprocal(/*input*/ char, /*output*/ char),
 }
 
 impl fmt::Display for SegmentError {
@@ -44,29 +33,51 @@ impl fmt::Display for SegmentError {
             ),
             SegmentError::MissedRingSegment(missed) => write!(f, "missed ring segment: {}", missed),
             SegmentError::InvalidNotch(notch) => write!(f, "invalid notch: {}", notch),
+            SegmentError::NotReciprocal(input, output) => write!(
+                f,
+                "reflector is not reciprocal: {} -> {}, but {} -> other",
+                input, output, output
+            ),
         }
     }
 }
 
 impl error::Error for SegmentError {}
 
-pub fn check_ring(outputs: &String) -> Result<(), SegmentError> {
-    if SEGMENTS.len() != outputs.len() {
-        return Err(SegmentError::InvalidRingSize(outputs.len(), SEGMENTS.len()));
+pub fn check_ring(alphabet: &Alphabet, outputs: &str) -> Result<(), SegmentError> {
+    let size = outputs.chars().count();
+    if alphabet.len() != size {
+        return Err(SegmentError::InvalidRingSize(size, alphabet.len()));
+    }
+
+    for val in alphabet.letters() {
+        if outputs.contains(*val) == false {
+            return Err(SegmentError::MissedRingSegment(*val));
+        }
     }
 
-    for val in SEGMENTS.chars() {
-        if outputs.contains(val) == false {
-            return Err(SegmentError::MissedRingSegment(val));
+    return Ok(());
+}
+
+// reflector sends signal back through the same wire, so its wiring must be a
+// set of pairs
+pub fn check_reflector(alphabet: &Alphabet, outputs: &str) -> Result<(), SegmentError> {
+    check_ring(alphabet, outputs)?;
+
+    let ring: Vec<char> = outputs.chars().collect();
+    for (input, output) in alphabet.letters().iter().zip(ring.iter()) {
+        let back = alphabet.index(*output).expect("ring is checked");
+        if ring[back] != *input {
+            return Err(SegmentError::NotReciprocal(*input, *output));
         }
     }
 
     return Ok(());
 }
 
-pub fn check_notches(notches: &String) -> Result<(), SegmentError> {
+pub fn check_notches(alphabet: &Alphabet, notches: &str) -> Result<(), SegmentError> {
     for notch in notches.chars() {
-        if SEGMENTS.contains(notch) == false {
+        if alphabet.letters().contains(&notch) == false {
             return Err(SegmentError::InvalidNotch(notch));
         }
     }
@@ -0,0 +1,191 @@
+use crate::device::builder::BuildError;
+use crate::device::builder::DeviceBuilder;
+use crate::device::builder::Machine;
+use crate::device::builder::Setting;
+use crate::device::details::ALPHABET;
+use crate::device::Device;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+// count of cables in war time key sheets
+pub const KEY_CABLES: usize = 10;
+
+// Full key of a historical machine, one line of a key sheet. Text form is
+// fields separated by `|`, like:
+//
+// enigma-i | B | II IV V | BUL | BLA | AV BS CG DL FU HZ IN KM OW RX
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    pub machine: Machine,
+    pub reflector: String,
+    pub walzenlage: Vec<String>,
+    pub ringstellung: String,
+    pub grundstellung: String,
+    pub steckers: String,
+}
+
+impl Key {
+    // random key with different rotors and 10 cables
+    pub fn random<R: Rng>(machine: Machine, rng: &mut R) -> Result<Self, BuildError> {
+        let (reflectors, count) = match (machine.reflectors(), machine.rotor_count()) {
+            (Some(reflectors), Some(count)) => (reflectors, count),
+            _ => {
+                return Err(BuildError::single(
+                    Setting::Machine,
+                    format!("random keys are not available for {} machine", machine),
+                ))
+            }
+        };
+
+        let mut walzenlage: Vec<String> = Vec::new();
+        for slot in 0..count {
+            let rotors = machine.rotors(slot).expect("historical machine");
+            let free: Vec<&&str> = rotors
+                .iter()
+                .filter(|rotor| walzenlage.iter().any(|used| used == **rotor) == false)
+                .collect();
+            walzenlage.push(free.choose(rng).expect("enough rotors").to_string());
+        }
+
+        let letters: Vec<char> = ALPHABET.chars().collect();
+        let position = |rng: &mut R| -> String {
+            return (0..count)
+                .map(|_| *letters.choose(rng).expect("not empty alphabet"))
+                .collect();
+        };
+        let ringstellung = position(rng);
+        let grundstellung = position(rng);
+
+        let mut shuffled = letters.clone();
+        shuffled.shuffle(rng);
+        let steckers = shuffled[..KEY_CABLES * 2]
+            .chunks(2)
+            .map(|pair| pair.iter().collect::<String>())
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        return Ok(Self {
+            machine,
+            reflector: reflectors.choose(rng).expect("not empty").to_string(),
+            walzenlage,
+            ringstellung,
+            grundstellung,
+            steckers,
+        });
+    }
+
+    // keys for every day of a month
+    pub fn sheet<R: Rng>(
+        machine: Machine,
+        days: usize,
+        rng: &mut R,
+    ) -> Result<Vec<Self>, BuildError> {
+        let mut keys = Vec::new();
+        for _ in 0..days {
+            keys.push(Self::random(machine, rng)?);
+        }
+        return Ok(keys);
+    }
+
+    pub fn builder(&self) -> DeviceBuilder {
+        return DeviceBuilder::new(self.machine)
+            .reflector(&self.reflector)
+            .walzenlage(&self.walzenlage)
+            .ringstellung(&self.ringstellung)
+            .grundstellung(&self.grundstellung)
+            .steckers(&self.steckers);
+    }
+
+    pub fn device(&self) -> Result<Device, BuildError> {
+        return self.builder().build();
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} | {} | {} | {} | {} | {}",
+            self.machine,
+            self.reflector,
+            self.walzenlage.join(" "),
+            self.ringstellung,
+            self.grundstellung,
+            self.steckers
+        )
+    }
+}
+
+impl FromStr for Key {
+    type Err = BuildError;
+
+    // fields are only split here, settings are checked by builder
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split('|').map(|field| field.trim()).collect();
+        if fields.len() != 6 {
+            return Err(BuildError::single(
+                Setting::Machine,
+                format!("expected 6 fields of key, but have {}", fields.len()),
+            ));
+        }
+
+        return Ok(Self {
+            machine: fields[0].parse()?,
+            reflector: fields[1].to_string(),
+            walzenlage: fields[2].split_whitespace().map(String::from).collect(),
+            ringstellung: fields[3].to_string(),
+            grundstellung: fields[4].to_string(),
+            steckers: fields[5].to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device::builder::Machine;
+    use crate::device::key::Key;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn random_keys() {
+        let mut rng = StdRng::seed_from_u64(1941);
+        for machine in [Machine::EnigmaI, Machine::M3, Machine::M4] {
+            for key in Key::sheet(machine, 31, &mut rng).unwrap() {
+                let device = key.device().unwrap();
+                assert_eq!(device.cables().len(), 29);
+                assert_eq!(device.segments(), key.grundstellung);
+            }
+        }
+        assert!(Key::random(Machine::Custom, &mut rng).is_err());
+
+        // same seed gives same keys
+        let key = Key::random(Machine::M4, &mut StdRng::seed_from_u64(7)).unwrap();
+        assert_eq!(
+            key,
+            Key::random(Machine::M4, &mut StdRng::seed_from_u64(7)).unwrap()
+        );
+        assert!(key.walzenlage[0] == "BETA" || key.walzenlage[0] == "GAMMA");
+    }
+
+    #[test]
+    fn parse() {
+        let line = "enigma-i | B | II IV V | BUL | BLA | AV BS CG DL FU HZ IN KM OW RX";
+        let key: Key = line.parse().unwrap();
+        assert_eq!(key.walzenlage, ["II", "IV", "V"]);
+        assert_eq!(key.to_string(), line);
+
+        let mut device = key.device().unwrap();
+        assert_eq!(device.crypt('E').unwrap(), 'A');
+
+        assert!("m3 | B | I II III".parse::<Key>().is_err());
+        assert!("m5 | B | I II III | AAA | AAA | ".parse::<Key>().is_err());
+        assert!("m3 | B | I II | AAA | AAA | "
+            .parse::<Key>()
+            .unwrap()
+            .device()
+            .is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::device::block::Block;
+use crate::device::rotor::Rotor;
+use std::error;
+use std::fmt;
+
+// analysis enumerates every position of the block, so it is limited to
+// reasonable count of rotors (4 rotors with 26 segments)
+pub const MAX_STATES: usize = 456976;
+
+// Stepping of a block is a function from positions to positions, so starting
+// from any position the block gets to some cycle of positions and repeats it
+// forever. Because of double step and multi-notch rotors the cycle is shorter
+// than 26^n, and some positions can be set by operator, but never reached again.
+#[derive(Debug, Clone)]
+pub struct Cycle {
+    pub start: String, // first (in alphabetical order) positions of the cycle
+    pub length: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct PeriodAnalysis {
+    pub cycles: Vec<Cycle>,
+    pub reachable: Vec<String>,   // positions that lay on cycles
+    pub unreachable: Vec<String>, // start positions that are never reached again
+}
+
+#[derive(Debug, Clone)]
+pub enum PeriodError {
+    TooManyStates(/*actual*/ usize, /*max*/ usize),
+}
+
+impl fmt::Display for PeriodError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PeriodError::TooManyStates(actual, max) => write!(
+                f,
+                "too many rotor positions for analysis: {}/{} (actual/max)",
+                actual, max
+            ),
+        }
+    }
+}
+
+impl error::Error for PeriodError {}
+
+impl PeriodAnalysis {
+    // the longest cycle, or the only one for usual machines
+    pub fn period(&self) -> usize {
+        return self
+            .cycles
+            .iter()
+            .map(|cycle| cycle.length)
+            .max()
+            .unwrap_or(0);
+    }
+}
+
+// stators never turn, so only moving rotors are analyzed and positions are
+// reported without stators
+pub fn analyze(block: &Block) -> Result<PeriodAnalysis, PeriodError> {
+    let slots: Vec<usize> = (0..block.rotors().len())
+        .filter(|slot| block.pawls()[*slot])
+        .collect();
+    let rotors: Vec<Rotor> = slots
+        .iter()
+        .map(|slot| block.rotors()[*slot].clone())
+        .collect();
+    let rotors = &rotors[..];
+    let states = rotors
+        .iter()
+        .try_fold(1_usize, |states, rotor| {
+            states.checked_mul(rotor.alphabet().len())
+        })
+        .unwrap_or(usize::MAX);
+    if states > MAX_STATES {
+        return Err(PeriodError::TooManyStates(states, MAX_STATES));
+    }
+
+    // next state for every state
+    // stators keep their positions
+    let mut block = block.clone();
+    let mut all = block.positions();
+    let mut next = Vec::with_capacity(states);
+    for state in 0..states {
+        for (slot, position) in slots.iter().zip(positions(state, rotors)) {
+            all[*slot] = position;
+        }
+        block
+            .set_positions(&all)
+            .expect("count of positions is same as count of rotors");
+        block.advance();
+        let moved: Vec<usize> = slots.iter().map(|slot| block.positions()[*slot]).collect();
+        next.push(index(&moved, rotors));
+    }
+
+    // 0 - not visited, 1 - on current path, 2 - done
+    let mut marks = vec![0_u8; states];
+    let mut on_cycle = vec![false; states];
+    let mut cycles = Vec::new();
+    let mut path = Vec::new();
+
+    for state in 0..states {
+        let mut current = state;
+        while marks[current] == 0 {
+            marks[current] = 1;
+            path.push(current);
+            current = next[current];
+        }
+
+        if marks[current] == 1 {
+            // current path closed into new cycle
+            let mut length = 0;
+            let mut first = current;
+            let mut member = current;
+            loop {
+                on_cycle[member] = true;
+                first = first.min(member);
+                length += 1;
+                member = next[member];
+                if member == current {
+                    break;
+                }
+            }
+
+            cycles.push(Cycle {
+                start: segments(first, rotors),
+                length,
+            });
+        }
+
+        for member in path.drain(..) {
+            marks[member] = 2;
+        }
+    }
+
+    let mut reachable = Vec::new();
+    let mut unreachable = Vec::new();
+    for (state, on_cycle) in on_cycle.into_iter().enumerate() {
+        if on_cycle {
+            reachable.push(segments(state, rotors));
+        } else {
+            unreachable.push(segments(state, rotors));
+        }
+    }
+
+    cycles.sort_by(|a, b| b.length.cmp(&a.length).then(a.start.cmp(&b.start)));
+
+    return Ok(PeriodAnalysis {
+        cycles,
+        reachable,
+        unreachable,
+    });
+}
+
+// state index to positions of rotors, left rotor is the most significant
+fn positions(mut state: usize, rotors: &[Rotor]) -> Vec<usize> {
+    let mut retval = vec![0; rotors.len()];
+    for (position, rotor) in retval.iter_mut().zip(rotors).rev() {
+        *position = state % rotor.alphabet().len();
+        state /= rotor.alphabet().len();
+    }
+    return retval;
+}
+
+fn index(positions: &[usize], rotors: &[Rotor]) -> usize {
+    return positions
+        .iter()
+        .zip(rotors)
+        .fold(0, |state, (position, rotor)| {
+            state * rotor.alphabet().len() + position
+        });
+}
+
+fn segments(state: usize, rotors: &[Rotor]) -> String {
+    return positions(state, rotors)
+        .iter()
+        .zip(rotors)
+        .map(|(position, rotor)| rotor.alphabet().letter(*position))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device::block::Block;
+    use crate::device::block::Stepping;
+    use crate::device::period::analyze;
+    use crate::device::rotor::Rotor;
+
+    fn block(rotors: &[&str], stepping: Stepping) -> Block {
+        let mut block = Block::new();
+        block.set_stepping(stepping);
+        for rotor in rotors {
+            block.add_rotor(Rotor::model(rotor).unwrap());
+        }
+        return block;
+    }
+
+    #[test]
+    fn ratchet() {
+        let analysis = analyze(&block(&["I", "II", "III"], Stepping::Ratchet)).unwrap();
+
+        assert_eq!(analysis.cycles.len(), 1);
+        assert_eq!(analysis.period(), 26 * 25 * 26);
+        assert_eq!(analysis.reachable.len(), 26 * 25 * 26);
+        assert_eq!(analysis.unreachable.len(), 26 * 26);
+
+        // middle rotor at notch (E for rotor II) can't be reached after the
+        // right rotor passed its own notch
+        assert!(analysis.unreachable.contains(&"AEA".to_string()));
+        assert!(analysis.reachable.contains(&"AEV".to_string()) == false);
+        assert!(analysis.reachable.contains(&"AAA".to_string()));
+    }
+
+    #[test]
+    fn ratchet_multi_notch() {
+        let analysis = analyze(&block(&["VI", "VII", "VIII"], Stepping::Ratchet)).unwrap();
+
+        // with two notches 13 segments apart rotors keep their relative phase,
+        // so positions split into independent cycles
+        assert_eq!(analysis.cycles.len(), 4);
+        for cycle in analysis.cycles.iter() {
+            assert_eq!(cycle.length, 26 * 12 * 13);
+        }
+        assert_eq!(analysis.cycles[0].start, "AAB");
+        assert_eq!(analysis.reachable.len(), 4 * 26 * 12 * 13);
+        assert_eq!(
+            analysis.reachable.len() + analysis.unreachable.len(),
+            26 * 26 * 26
+        );
+    }
+
+    #[test]
+    fn gear_and_odometer() {
+        for stepping in [Stepping::Gear, Stepping::Odometer] {
+            let analysis = analyze(&block(&["I", "II", "III"], stepping)).unwrap();
+
+            assert_eq!(analysis.cycles.len(), 1);
+            assert_eq!(analysis.period(), 26 * 26 * 26);
+            assert!(analysis.unreachable.is_empty());
+        }
+    }
+
+    #[test]
+    fn stators_are_ignored() {
+        let mut block = block(&["I", "II", "III"], Stepping::Ratchet);
+        block.add_stator(Rotor::model("IV").unwrap());
+        block.add_stator(Rotor::model("V").unwrap());
+
+        let analysis = analyze(&block).unwrap();
+        assert_eq!(analysis.period(), 26 * 25 * 26);
+        assert_eq!(analysis.reachable[0], "AAA");
+    }
+
+    #[test]
+    fn rotors_without_pawl_are_ignored() {
+        // thin rotor of M4
+        let mut block = block(&["BETA", "I", "II", "III"], Stepping::Ratchet);
+        block.set_pawls(&[false, true, true, true]).unwrap();
+        let analysis = analyze(&block).unwrap();
+        assert_eq!(analysis.period(), 26 * 25 * 26);
+        assert_eq!(analysis.reachable.len(), 26 * 25 * 26);
+    }
+
+    #[test]
+    fn too_many_rotors() {
+        analyze(&block(&["I", "II", "III", "IV", "V"], Stepping::Ratchet)).unwrap_err();
+    }
+}
@@ -1,10 +1,17 @@
-use crate::device::details::ALPHABET;
+use crate::device::alphabet::Alphabet;
+use crate::device::details::CorePlugBoard;
+use crate::device::uhr::Uhr;
 use std::error;
 use std::fmt;
 
+#[derive(Debug, Clone)]
 pub struct PlugBoard {
+    alphabet: Alphabet,
+    // cables in order of adding, both directions of every cable
     inputs: String,
     outputs: String,
+    board: CorePlugBoard, // same cables by indexes, swaps letters
+    uhr: Option<Uhr>,     // replaces cables, if set
 }
 
 #[derive(Debug, Clone)]
@@ -13,6 +20,14 @@ pub enum PlugBoardError {
     Duplicate(char),
     SameCharacters,
     PairCountMismatch,
+    InvalidCable(
+        /*token*/ String,
+        /*offset*/ usize,
+        /*reason*/ Box<PlugBoardError>,
+    ),
+    TooManyCables(/*actual*/ usize, /*max*/ usize),
+    UhrConflict,
+    NotConnected(char),
 }
 
 impl fmt::Display for PlugBoardError {
@@ -28,20 +43,55 @@ impl fmt::Display for PlugBoardError {
             PlugBoardError::PairCountMismatch => {
                 write!(f, "count of characters in pair settings should be even")
             }
+            PlugBoardError::InvalidCable(token, offset, reason) => {
+                write!(f, "invalid cable \"{}\" at {}: {}", token, offset, reason)
+            }
+            PlugBoardError::TooManyCables(actual, max) => {
+                write!(f, "too many cables: {}/{} (actual/max)", actual, max)
+            }
+            PlugBoardError::UhrConflict => {
+                write!(f, "plugboard can not have both cables and Uhr")
+            }
+            PlugBoardError::NotConnected(ch) => {
+                write!(f, "plugboard have no connection for: {}", ch)
+            }
         }
     }
 }
 
 impl error::Error for PlugBoardError {}
 
+// boards are same if they connect same letters, order of cables doesn't
+// matter
+impl PartialEq for PlugBoard {
+    fn eq(&self, other: &Self) -> bool {
+        return self.alphabet == other.alphabet
+            && self.uhr == other.uhr
+            && self.board == other.board;
+    }
+}
+
+impl Eq for PlugBoard {}
+
 impl PlugBoard {
     pub fn new() -> Self {
+        return Self::with_alphabet(&Alphabet::latin());
+    }
+
+    pub fn with_alphabet(alphabet: &Alphabet) -> Self {
         Self {
+            alphabet: alphabet.clone(),
             inputs: String::new(),
             outputs: String::new(),
+            board: CorePlugBoard::with_size(alphabet.len()).expect("size of alphabet"),
+            uhr: None,
         }
     }
 
+    pub fn alphabet(self: &PlugBoard) -> &Alphabet {
+        return &self.alphabet;
+    }
+
     // return added plug pairs
     pub fn pairs(self: &PlugBoard) -> &String {
         return &self.inputs;
@@ -52,17 +102,21 @@ impl PlugBoard {
         inp: char,
         out: char,
     ) -> Result<(char, char), PlugBoardError> {
-        let input = inp.to_ascii_uppercase();
-        let output = out.to_ascii_uppercase();
+        let input = self
+            .alphabet
+            .normalize(inp)
+            .ok_or(PlugBoardError::InvalidCharacter(inp))?;
+        let output = self
+            .alphabet
+            .normalize(out)
+            .ok_or(PlugBoardError::InvalidCharacter(out))?;
 
         if input == output {
             return Err(PlugBoardError::SameCharacters);
         }
 
-        if ALPHABET.contains(input) == false {
-            return Err(PlugBoardError::InvalidCharacter(input));
-        } else if ALPHABET.contains(output) == false {
-            return Err(PlugBoardError::InvalidCharacter(output));
+        if self.uhr.is_some() {
+            return Err(PlugBoardError::UhrConflict);
         }
 
         if self.inputs.contains(input) {
@@ -79,52 +133,255 @@ impl PlugBoard {
         self.inputs.push(output);
         self.outputs.push(input);
 
+        let (first, second) = (self.alphabet.index(input), self.alphabet.index(output));
+        self.board
+            .plug(first.unwrap() as u8, second.unwrap() as u8)
+            .expect("checked pair");
+
         return Ok((input, output));
     }
 
+    // add plug pairs to existing ones. Nothing is added if any of pairs is
+    // invalid
     pub fn set_plug_pairs(&mut self, plug_pairs: &str) -> Result<&String, PlugBoardError> {
-        if plug_pairs.len() % 2 != 0 {
+        let chars: Vec<char> = plug_pairs.chars().collect();
+        if !chars.len().is_multiple_of(2) {
             return Err(PlugBoardError::PairCountMismatch);
         }
 
-        for i in 0..(plug_pairs.len() / 2) {
-            let input = plug_pairs.chars().nth(i * 2).unwrap();
-            let output = plug_pairs.chars().nth(i * 2 + 1).unwrap();
-
-            self.add_pair(input, output)?;
+        let mut board = self.clone();
+        for (index, pair) in chars.chunks(2).enumerate() {
+            board.add_pair(pair[0], pair[1]).map_err(|err| {
+                // offset of the character that failed, not of the pair
+                let first = match &err {
+                    PlugBoardError::InvalidCharacter(ch) => *ch == pair[0],
+                    PlugBoardError::Duplicate(ch) => self.alphabet.normalize(pair[0]) == Some(*ch),
+                    PlugBoardError::SameCharacters => false,
+                    _ => true,
+                };
+                let offset = if first { index * 2 } else { index * 2 + 1 };
+                PlugBoardError::InvalidCable(pair.iter().collect(), offset, Box::new(err))
+            })?;
         }
+        *self = board;
 
         return Ok(self.pairs());
     }
 
-    pub fn crypt(self: &Self, ch: char) -> Result<char, PlugBoardError> {
-        let val = ch.to_ascii_uppercase();
+    // unplug cable connected to the letter, return the removed pair
+    pub fn remove_pair(self: &mut Self, ch: char) -> Result<(char, char), PlugBoardError> {
+        let val = self
+            .alphabet
+            .normalize(ch)
+            .ok_or(PlugBoardError::InvalidCharacter(ch))?;
 
-        if ALPHABET.contains(val) == false {
-            return Err(PlugBoardError::InvalidCharacter(ch));
+        if self.uhr.is_some() {
+            return Err(PlugBoardError::UhrConflict);
         }
 
-        match self.inputs.find(val) {
-            Some(pos) => {
-                return Ok(self.outputs.chars().nth(pos).unwrap());
-            }
-            None => (),
+        let mut pairs: Vec<char> = self.inputs.chars().collect();
+        let pos = pairs
+            .iter()
+            .position(|input| *input == val)
+            .ok_or(PlugBoardError::NotConnected(val))?;
+
+        // every cable is stored as forward and backward pair, next to each other
+        let start = pos - pos % 2;
+        let removed: Vec<char> = pairs.drain(start..start + 2).collect();
+        self.inputs = pairs.iter().collect();
+        self.outputs = pairs
+            .chunks(2)
+            .flat_map(|pair| [pair[1], pair[0]])
+            .collect();
+
+        self.board = CorePlugBoard::with_size(self.alphabet.len()).expect("size of alphabet");
+        for pair in self.alphabet.indexes(&self.inputs).chunks(2) {
+            self.board.plug(pair[0], pair[1]).expect("plugged before");
         }
 
-        match ALPHABET.find(val) {
-            Some(_) => {
-                return Ok(val);
+        return Ok((removed[0], removed[1]));
+    }
+
+    // move end of the cable from letter `from` to letter `to`, other end of the
+    // cable stays in place. Return the new pair, board is not changed on error
+    pub fn rewire(self: &mut Self, from: char, to: char) -> Result<(char, char), PlugBoardError> {
+        let mut board = self.clone();
+        let (input, output) = board.remove_pair(from)?;
+        let other = if self.alphabet.normalize(from) == Some(input) {
+            output
+        } else {
+            input
+        };
+
+        let pair = board.add_pair(other, to)?;
+        *self = board;
+
+        return Ok(pair);
+    }
+
+    // remove all cables and Uhr box
+    pub fn clear(self: &mut Self) {
+        *self = Self::with_alphabet(&self.alphabet);
+    }
+
+    pub fn uhr(self: &Self) -> Option<&Uhr> {
+        return self.uhr.as_ref();
+    }
+
+    pub fn uhr_mut(self: &mut Self) -> Option<&mut Uhr> {
+        return self.uhr.as_mut();
+    }
+
+    // connect letters through Uhr box instead of cables
+    pub fn set_uhr(self: &mut Self, uhr: Uhr) -> Result<(), PlugBoardError> {
+        if self.inputs.is_empty() == false {
+            return Err(PlugBoardError::UhrConflict);
+        }
+        self.uhr = Some(uhr);
+        return Ok(());
+    }
+
+    // cables in key sheet notation, like "AV BS CG DL"
+    pub fn cables(self: &Self) -> String {
+        if let Some(uhr) = &self.uhr {
+            return uhr.cables();
+        }
+
+        let pairs: Vec<char> = self.inputs.chars().collect();
+        return pairs
+            .chunks(2)
+            .map(|pair| pair.iter().collect::<String>())
+            .collect::<Vec<String>>()
+            .join(" ");
+    }
+
+    // parse cables in key sheet notation, like "AV BS CG DL". Pairs can be
+    // separated by spaces, commas or hyphens, run of pairs without separators
+    // is accepted too. Cables are checked against each other and current
+    // connections of the board, and count of all cables is limited by `max`
+    pub fn parse_cables(
+        self: &Self,
+        cables: &str,
+        max: Option<usize>,
+    ) -> Result<Vec<(char, char)>, PlugBoardError> {
+        let mut retval = Vec::<(char, char)>::new();
+        let mut used: Vec<char> = self.inputs.chars().collect();
+
+        let mut token = String::new();
+        let mut offset = 0;
+        for (index, ch) in cables.chars().chain([' ']).enumerate() {
+            if ch.is_whitespace() == false && ch != ',' && ch != '-' {
+                if token.is_empty() {
+                    offset = index;
+                }
+                token.push(ch);
+                continue;
+            }
+            if token.is_empty() {
+                continue;
             }
-            None => {
-                return Err(PlugBoardError::InvalidCharacter(ch));
+
+            // offset of the character that failed, like by set_plug_pairs
+            let invalid = |index: usize, reason: PlugBoardError| {
+                PlugBoardError::InvalidCable(token.clone(), offset + index, Box::new(reason))
+            };
+
+            let chars: Vec<char> = token.chars().collect();
+            if chars.len().is_multiple_of(2) == false {
+                return Err(invalid(chars.len() - 1, PlugBoardError::PairCountMismatch));
             }
+
+            for (pair_index, pair) in chars.chunks(2).enumerate() {
+                let mut cable = Vec::new();
+                for (char_index, ch) in pair.iter().enumerate() {
+                    let index = pair_index * 2 + char_index;
+                    let val = self
+                        .alphabet
+                        .normalize(*ch)
+                        .ok_or_else(|| invalid(index, PlugBoardError::InvalidCharacter(*ch)))?;
+                    if used.contains(&val) {
+                        return Err(invalid(index, PlugBoardError::Duplicate(val)));
+                    }
+                    if cable.contains(&val) {
+                        return Err(invalid(index, PlugBoardError::SameCharacters));
+                    }
+                    cable.push(val);
+                }
+
+                used.extend_from_slice(&cable);
+                retval.push((cable[0], cable[1]));
+            }
+
+            token.clear();
+        }
+
+        let count = self.inputs.chars().count() / 2 + retval.len();
+        match max {
+            Some(max) if count > max => return Err(PlugBoardError::TooManyCables(count, max)),
+            _ => (),
+        }
+
+        return Ok(retval);
+    }
+
+    // add cables in key sheet notation, see parse_cables. Nothing is added if
+    // any of cables is invalid
+    pub fn set_cables(
+        self: &mut Self,
+        cables: &str,
+        max: Option<usize>,
+    ) -> Result<String, PlugBoardError> {
+        for (input, output) in self.parse_cables(cables, max)? {
+            self.add_pair(input, output)?;
+        }
+
+        return Ok(self.cables());
+    }
+
+    // replace all connections of the board by cables in key sheet notation.
+    // The board keeps old connections if any of cables is invalid
+    pub fn replace_cables(
+        self: &mut Self,
+        cables: &str,
+        max: Option<usize>,
+    ) -> Result<String, PlugBoardError> {
+        let mut board = Self::with_alphabet(&self.alphabet);
+        board.set_cables(cables, max)?;
+        *self = board;
+
+        return Ok(self.cables());
+    }
+
+    // from keyboard to rotors
+    pub fn crypt(self: &Self, ch: char) -> Result<char, PlugBoardError> {
+        if let Some(uhr) = &self.uhr {
+            return uhr.forward(ch);
+        }
+
+        let index = self
+            .alphabet
+            .index(ch)
+            .ok_or(PlugBoardError::InvalidCharacter(ch))?;
+
+        let output = self.board.swap(index as u8);
+        return Ok(self.alphabet.letter(output as usize));
+    }
+
+    // from rotors to lamps, same as crypt for cables, but Uhr connections are
+    // not reciprocal
+    pub fn backward(self: &Self, ch: char) -> Result<char, PlugBoardError> {
+        if let Some(uhr) = &self.uhr {
+            return uhr.backward(ch);
         }
+        return self.crypt(ch);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::device::plugboard::PlugBoard;
+    use crate::device::plugboard::PlugBoardError;
+    use crate::device::uhr::Uhr;
 
     #[test]
     fn add_pair() {
@@ -154,4 +411,206 @@ mod tests {
 
         assert_eq!(board.crypt('A').unwrap(), 'B');
     }
+
+    #[test]
+    fn equality() {
+        let mut board = PlugBoard::new();
+        board.set_cables("AB CD", None).unwrap();
+        let mut other = PlugBoard::new();
+        other.set_cables("dc ba", None).unwrap();
+        assert_eq!(board, other);
+
+        other.rewire('D', 'E').unwrap();
+        assert_ne!(board, other);
+    }
+
+    #[test]
+    fn uhr() {
+        let mut board = PlugBoard::new();
+        board
+            .set_uhr(Uhr::new("AT BL DF GJ HM NW OP QY RZ VX", 1).unwrap())
+            .unwrap();
+        assert_eq!(board.crypt('A').unwrap(), 'F');
+        assert_eq!(board.backward('F').unwrap(), 'A');
+        assert_eq!(board.crypt('C').unwrap(), 'C');
+        assert_eq!(board.cables(), "AT BL DF GJ HM NW OP QY RZ VX");
+        board.add_pair('C', 'E').unwrap_err();
+
+        let mut board = PlugBoard::new();
+        board.add_pair('C', 'E').unwrap();
+        board
+            .set_uhr(Uhr::new("AT BL DF GJ HM NW OP QY RZ VX", 1).unwrap())
+            .unwrap_err();
+    }
+
+    #[test]
+    fn set_cables() {
+        for cables in [
+            "AV BS CG DL",
+            "av,bs, cg ,dl",
+            "AV-BS-CG-DL",
+            " AVBS  CGDL ",
+        ] {
+            let mut board = PlugBoard::new();
+            assert_eq!(board.set_cables(cables, Some(10)).unwrap(), "AV BS CG DL");
+            assert_eq!(board.crypt('V').unwrap(), 'A');
+            assert_eq!(board.crypt('L').unwrap(), 'D');
+        }
+
+        let mut board = PlugBoard::new();
+        assert_eq!(board.set_cables("", None).unwrap(), "");
+        assert_eq!(board.cables(), "");
+        board.set_cables("AB", None).unwrap();
+        board.set_cables("CD EF", None).unwrap();
+        assert_eq!(board.cables(), "AB CD EF");
+    }
+
+    #[test]
+    fn check_cables_failure() {
+        let board = PlugBoard::new();
+
+        let offending = |cables: &str| match board.parse_cables(cables, None) {
+            Err(PlugBoardError::InvalidCable(token, offset, _)) => (token, offset),
+            _ => panic!("expected invalid cable for: {}", cables),
+        };
+
+        // offset of the offending character, not of the token
+        assert_eq!(offending("AV BS CGX DL"), ("CGX".to_string(), 8));
+        assert_eq!(offending("AV 1B"), ("1B".to_string(), 3));
+        assert_eq!(offending("AV B1"), ("B1".to_string(), 4));
+        assert_eq!(offending("AV,BA"), ("BA".to_string(), 4));
+        assert_eq!(offending("AV QQ"), ("QQ".to_string(), 4));
+        assert_eq!(offending("AVBSCA"), ("AVBSCA".to_string(), 5));
+
+        let mut board = PlugBoard::new();
+        board.add_pair('A', 'V').unwrap();
+        assert_eq!(
+            board.parse_cables("XY VQ", None).unwrap_err().to_string(),
+            "invalid cable \"VQ\" at 3: plugboard already have connection for: V"
+        );
+    }
+
+    #[test]
+    fn max_cables() {
+        let mut board = PlugBoard::new();
+        let cables = "AB CD EF GH IJ KL MN OP QR ST";
+        board.set_cables(cables, Some(10)).unwrap();
+
+        match board.set_cables("UV", Some(10)) {
+            Err(PlugBoardError::TooManyCables(11, 10)) => (),
+            _ => panic!("expected too many cables"),
+        }
+        assert_eq!(board.cables(), cables);
+
+        let mut board = PlugBoard::new();
+        board
+            .set_cables("AB CD EF GH IJ KL MN OP QR ST UV WX YZ", Some(13))
+            .unwrap();
+    }
+
+    #[test]
+    fn remove_pair() {
+        let mut board = PlugBoard::new();
+        board.set_cables("AV BS CG DL", None).unwrap();
+
+        assert_eq!(board.remove_pair('s').unwrap(), ('B', 'S'));
+        assert_eq!(board.cables(), "AV CG DL");
+        assert_eq!(board.crypt('B').unwrap(), 'B');
+        assert_eq!(board.crypt('S').unwrap(), 'S');
+        assert_eq!(board.crypt('G').unwrap(), 'C');
+        assert_eq!(board.crypt('L').unwrap(), 'D');
+
+        match board.remove_pair('B') {
+            Err(PlugBoardError::NotConnected('B')) => (),
+            _ => panic!("expected not connected"),
+        }
+        board.remove_pair('1').unwrap_err();
+
+        let mut board = PlugBoard::new();
+        board
+            .set_uhr(Uhr::new("AT BL DF GJ HM NW OP QY RZ VX", 1).unwrap())
+            .unwrap();
+        board.remove_pair('A').unwrap_err();
+    }
+
+    #[test]
+    fn rewire() {
+        let mut board = PlugBoard::new();
+        board.set_cables("AV BS CG", None).unwrap();
+
+        assert_eq!(board.rewire('V', 'Z').unwrap(), ('A', 'Z'));
+        assert_eq!(board.cables(), "BS CG AZ");
+        assert_eq!(board.crypt('Z').unwrap(), 'A');
+        assert_eq!(board.crypt('V').unwrap(), 'V');
+
+        assert_eq!(board.rewire('b', 'x').unwrap(), ('S', 'X'));
+        assert_eq!(board.crypt('X').unwrap(), 'S');
+
+        // failed rewire keeps the cable
+        board.rewire('C', 'Z').unwrap_err();
+        board.rewire('C', 'G').unwrap_err();
+        board.rewire('E', 'F').unwrap_err();
+        assert_eq!(board.cables(), "CG AZ SX");
+    }
+
+    #[test]
+    fn clear_and_replace() {
+        let mut board = PlugBoard::new();
+        board.set_cables("AV BS", None).unwrap();
+
+        assert_eq!(board.replace_cables("CG DL", None).unwrap(), "CG DL");
+        assert_eq!(board.crypt('A').unwrap(), 'A');
+
+        // invalid cables don't change the board
+        board.replace_cables("AB CC", None).unwrap_err();
+        board.replace_cables("AB CD EF", Some(2)).unwrap_err();
+        assert_eq!(board.cables(), "CG DL");
+        board.set_plug_pairs("ABCX").unwrap_err();
+        assert_eq!(board.cables(), "CG DL");
+
+        board.clear();
+        assert_eq!(board.cables(), "");
+        assert_eq!(board.crypt('C').unwrap(), 'C');
+
+        board
+            .set_uhr(Uhr::new("AT BL DF GJ HM NW OP QY RZ VX", 1).unwrap())
+            .unwrap();
+        assert_eq!(board.replace_cables("AB", None).unwrap(), "AB");
+        assert!(board.uhr().is_none());
+    }
+
+    #[test]
+    fn multi_byte_plug_pairs() {
+        let mut board = PlugBoard::new();
+        match board.set_plug_pairs("ABÄC") {
+            Err(PlugBoardError::InvalidCable(pair, 2, _)) => assert_eq!(pair, "ÄC"),
+            _ => panic!("expected invalid cable"),
+        }
+        board.set_plug_pairs("ÄBC").unwrap_err();
+        assert_eq!(board.pairs(), "");
+
+        // offset of the invalid character in the second pair
+        for (pairs, offset) in [
+            ("ABC1", 3),
+            ("AB1C", 2),
+            ("ABCA", 3),
+            ("ABBC", 2),
+            ("ABCC", 3),
+        ] {
+            match board.set_plug_pairs(pairs) {
+                Err(PlugBoardError::InvalidCable(_, actual, _)) => {
+                    assert_eq!(actual, offset, "{}", pairs)
+                }
+                _ => panic!("expected invalid cable"),
+            }
+        }
+
+        let mut board = PlugBoard::new();
+        assert_eq!(
+            board.parse_cables("AB ｃd", None).unwrap_err().to_string(),
+            "invalid cable \"ｃd\" at 3: invalid character: ｃ"
+        );
+        let cables = board.alphabet().transliterate("AB ｃd");
+        assert_eq!(board.set_cables(&cables, None).unwrap(), "AB CD");
+    }
 }
@@ -1,15 +1,19 @@
-use crate::device::details::check_ring;
-use crate::device::details::RING_A;
-use crate::device::details::RING_B;
-use crate::device::details::RING_C;
-use crate::device::details::SEGMENTS;
+use crate::device::alphabet::Alphabet;
+use crate::device::details::check_reflector;
+use crate::device::details::CoreReflector;
+use crate::device::details::REFLECTORS;
+use crate::device::details::RING_B_THIN;
+use crate::device::details::RING_C_THIN;
 use std::error;
 use std::fmt;
 
 // same as Rotor, but static
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Reflector {
-    // left side is always static, A-Z
+    alphabet: Alphabet,
+    // left side is always static, same as alphabet
     ring: String, // right side
+    core: CoreReflector,
 }
 
 #[derive(Debug, Clone)]
@@ -31,40 +35,57 @@ impl error::Error for ReflectorError {}
 
 impl Reflector {
     pub fn new(outputs: &str) -> Result<Self, Box<dyn error::Error>> {
-        let s = outputs.to_string().to_ascii_uppercase();
-        check_ring(&s)?;
+        return Self::with_alphabet(&Alphabet::latin(), outputs);
+    }
 
-        Ok(Self { ring: s })
+    pub fn with_alphabet(
+        alphabet: &Alphabet,
+        outputs: &str,
+    ) -> Result<Self, Box<dyn error::Error>> {
+        let s = alphabet.normalize_str(outputs);
+        check_reflector(alphabet, &s)?;
+
+        let core = CoreReflector::with_indexes(&alphabet.indexes(&s)).expect("checked ring");
+        Ok(Self {
+            alphabet: alphabet.clone(),
+            ring: s,
+            core,
+        })
     }
 
     pub fn model(s: &str) -> Result<Self, Box<dyn error::Error>> {
         let val = s.to_uppercase();
 
-        if val == "A" {
-            Self::new(RING_A)
-        } else if val == "B" {
-            Self::new(RING_B)
-        } else if val == "C" {
-            Self::new(RING_C)
-        } else {
-            Err(ReflectorError::InvalidReflectorType(val).into())
+        for (name, ring) in REFLECTORS.iter() {
+            if val == *name {
+                return Self::new(ring);
+            }
         }
+
+        Err(ReflectorError::InvalidReflectorType(val).into())
     }
 
-    pub fn forward(self: &Self, mut ch: char) -> Result<char, ReflectorError> {
-        ch.make_ascii_uppercase();
+    pub fn alphabet(self: &Self) -> &Alphabet {
+        return &self.alphabet;
+    }
 
-        let index = SEGMENTS
-            .find(ch)
-            .ok_or(ReflectorError::InvalidCharacter(ch))?;
+    // thin reflector of M4, it leaves room for the thin rotor
+    pub fn is_thin(self: &Self) -> bool {
+        return self.ring == RING_B_THIN || self.ring == RING_C_THIN;
+    }
+
+    pub fn wiring(self: &Self) -> &str {
+        return &self.ring;
+    }
 
-        let val = self
-            .ring
-            .chars()
-            .nth(index)
+    pub fn forward(self: &Self, ch: char) -> Result<char, ReflectorError> {
+        let index = self
+            .alphabet
+            .index(ch)
             .ok_or(ReflectorError::InvalidCharacter(ch))?;
 
-        Ok(val)
+        let output = self.core.reflect(index as u8);
+        Ok(self.alphabet.letter(output as usize))
     }
 
     pub fn backward(self: &Self, ch: char) -> Result<char, ReflectorError> {
@@ -76,6 +97,7 @@ impl Reflector {
 mod tests {
     use crate::device::details::SEGMENTS;
     use crate::device::Reflector;
+    use proptest::prelude::*;
 
     #[test]
     fn new_reflector() {
@@ -94,7 +116,7 @@ mod tests {
 
         for reflector in reflectors {
             for ch in SEGMENTS.chars() {
-                for input in vec![ch, ch.to_ascii_lowercase()] {
+                for input in [ch, ch.to_ascii_lowercase()] {
                     let intermediate = reflector.forward(input).unwrap();
                     let output = reflector.backward(intermediate).unwrap();
 
@@ -108,8 +130,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn not_reciprocal() {
+        // found by fuzzing: backward of not reciprocal wiring is not inverse
+        // of forward, so such wirings can not be used as reflector
+        assert!(Reflector::new("BCDEFGHIJKLMNOPQRSTUVWXYZA").is_err());
+        assert!(Reflector::new("AJDKSIRUXBLHWTMCQGZNPYFVOE").is_err());
+    }
+
     #[test]
     fn check_encryption_failure() {
         Reflector::model("A").unwrap().forward('$').unwrap_err();
     }
+
+    proptest! {
+        #[test]
+        fn reciprocal_reflector(
+            letters in Just(SEGMENTS.chars().collect::<Vec<char>>()).prop_shuffle(),
+        ) {
+            // pair neighbours of shuffled alphabet
+            let mut wiring: Vec<char> = SEGMENTS.chars().collect();
+            for pair in letters.chunks(2) {
+                wiring[(pair[0] as u8 - b'A') as usize] = pair[1];
+                wiring[(pair[1] as u8 - b'A') as usize] = pair[0];
+            }
+            let reflector = Reflector::new(&wiring.iter().collect::<String>()).unwrap();
+
+            for ch in SEGMENTS.chars() {
+                let out = reflector.forward(ch).unwrap();
+                prop_assert_ne!(out, ch);
+                prop_assert_eq!(reflector.forward(out).unwrap(), ch);
+                prop_assert_eq!(reflector.backward(ch).unwrap(), out);
+            }
+        }
+    }
 }
@@ -1,37 +1,30 @@
+use crate::device::alphabet::Alphabet;
 use crate::device::details::check_notches;
 use crate::device::details::check_ring;
-use crate::device::details::NOTCH_I;
-use crate::device::details::NOTCH_II;
-use crate::device::details::NOTCH_III;
-use crate::device::details::NOTCH_IV;
-use crate::device::details::NOTCH_V;
-use crate::device::details::NOTCH_VI;
-use crate::device::details::NOTCH_VII;
-use crate::device::details::NOTCH_VIII;
-use crate::device::details::RING_I;
-use crate::device::details::RING_II;
-use crate::device::details::RING_III;
-use crate::device::details::RING_IV;
-use crate::device::details::RING_V;
-use crate::device::details::RING_VI;
-use crate::device::details::RING_VII;
-use crate::device::details::RING_VIII;
-use crate::device::details::SEGMENTS;
+use crate::device::details::CoreRotor;
+use crate::device::details::RING_BETA;
+use crate::device::details::RING_GAMMA;
+use crate::device::details::ROTORS;
+use enigma_core::stepping::Wheel;
 use std::error;
 use std::fmt;
 
-// Rotor have 26 segments that represented by letters from A to Z. Right side
-// of each segment connected with left side of other segment via wire. Also
-// Rotor can have notches that turnover other ring on left side of the rotor
-// when current rotor position at the notch.
+// Rotor have segments that represented by letters of the alphabet, from A to Z
+// for historical machines. Right side of each segment connected with left
+// side of other segment via wire. Also Rotor can have notches that turnover
+// other ring on left side of the rotor when current rotor position at the
+// notch.
 // Reflector is a Rotor that can't rotate. Also it have connections only on
 // left side, so it sends signals back to rotors.
+// Signal goes through the rotor of the core, by indexes of letters.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Rotor {
-    // left side is always static, A-Z
-    ring: String,       // right side
-    notches: String,    // if current segment have notch, then it turnover rotor from left
-    position: usize,    // current segment
-    ring_offset: usize, // from 0 to sements.len()
+    alphabet: Alphabet,
+    // left side is always static, same as alphabet
+    ring: String,    // right side
+    notches: String, // if current segment have notch, then it turnover rotor from left
+    reversed: bool,  // inserted backwards
+    core: CoreRotor, // wiring, current segment and ring offset
 }
 
 #[derive(Debug, Clone)]
@@ -61,157 +54,230 @@ impl error::Error for RotorError {}
 
 impl Rotor {
     pub fn new(outputs: &str, notches: &str) -> Result<Self, Box<dyn error::Error>> {
-        let so = outputs.to_string().to_ascii_uppercase();
-        let sn = notches.to_string().to_ascii_uppercase();
-        check_ring(&so)?;
-        check_notches(&sn)?;
+        return Self::with_alphabet(&Alphabet::latin(), outputs, notches);
+    }
 
-        Ok(Self {
-            ring: so,
-            notches: sn,
-            position: 0,
-            ring_offset: 0,
-        })
+    pub fn with_alphabet(
+        alphabet: &Alphabet,
+        outputs: &str,
+        notches: &str,
+    ) -> Result<Self, Box<dyn error::Error>> {
+        let so = alphabet.normalize_str(outputs);
+        let sn = alphabet.normalize_str(notches);
+        check_ring(alphabet, &so)?;
+        check_notches(alphabet, &sn)?;
+
+        Ok(Self::build(alphabet, so, sn, false))
     }
 
+    // ring and notches must be checked
+    fn build(alphabet: &Alphabet, ring: String, notches: String, reversed: bool) -> Self {
+        let core = CoreRotor::with_indexes(&alphabet.indexes(&ring), &alphabet.indexes(&notches))
+            .expect("checked ring and notches");
+        Self {
+            alphabet: alphabet.clone(),
+            ring,
+            notches,
+            reversed,
+            core,
+        }
+    }
+
+    // model name with `'` suffix means reversed rotor, like "II'"
     pub fn model(s: &str) -> Result<Self, Box<dyn error::Error>> {
+        if let Some(model) = s.strip_suffix('\'') {
+            return Ok(Self::model(model)?.reversed());
+        }
+
         let val = s.to_uppercase();
 
-        if val == "I" {
-            Self::new(RING_I, NOTCH_I)
-        } else if val == "II" {
-            Self::new(RING_II, NOTCH_II)
-        } else if val == "III" {
-            Self::new(RING_III, NOTCH_III)
-        } else if val == "IV" {
-            Self::new(RING_IV, NOTCH_IV)
-        } else if val == "V" {
-            Self::new(RING_V, NOTCH_V)
-        } else if val == "VI" {
-            Self::new(RING_VI, NOTCH_VI)
-        } else if val == "VII" {
-            Self::new(RING_VII, NOTCH_VII)
-        } else if val == "VIII" {
-            Self::new(RING_VIII, NOTCH_VIII)
-        } else {
-            Err(RotorError::InvalidRotorType(val).into())
+        for (name, ring, notches) in ROTORS.iter() {
+            if val == *name {
+                return Self::new(ring, notches);
+            }
         }
+
+        Err(RotorError::InvalidRotorType(val).into())
+    }
+
+    // same rotor inserted backwards. Signal goes through wires in opposite
+    // direction and contacts are mirrored, so wiring W becomes M * W^-1 * M,
+    // where M(i) = -i. Notches are mirrored in same way. Position and ring
+    // offset are reset
+    pub fn reversed(self: &Self) -> Self {
+        let size = self.alphabet.len();
+        let mirror = |index: usize| (size - index) % size;
+
+        let mut ring = vec![' '; size];
+        for (input, output) in self.ring.chars().enumerate() {
+            let output = self.alphabet.index(output).unwrap();
+            ring[mirror(output)] = self.alphabet.letter(mirror(input));
+        }
+
+        let notches = self
+            .notches
+            .chars()
+            .map(|notch| {
+                self.alphabet
+                    .letter(mirror(self.alphabet.index(notch).unwrap()))
+            })
+            .collect();
+
+        Self::build(
+            &self.alphabet,
+            ring.into_iter().collect(),
+            notches,
+            self.reversed == false,
+        )
+    }
+
+    // thin rotor of M4 (beta or gamma), it fits only the left slot and has
+    // no pawl
+    pub fn is_thin(self: &Self) -> bool {
+        return self.ring == RING_BETA || self.ring == RING_GAMMA;
+    }
+
+    pub fn is_reversed(self: &Self) -> bool {
+        return self.reversed;
+    }
+
+    pub fn alphabet(self: &Self) -> &Alphabet {
+        return &self.alphabet;
+    }
+
+    // outputs for inputs A-Z at ring offset A, like "EKMFLGDQVZNTOWYHXUSPAIBRCJ"
+    pub fn wiring(self: &Self) -> &str {
+        return &self.ring;
+    }
+
+    pub fn notches(self: &Self) -> &str {
+        return &self.notches;
+    }
+
+    pub fn position(self: &Self) -> usize {
+        return self.core.segment() as usize;
+    }
+
+    pub fn set_position(self: &mut Self, position: usize) {
+        let position = position % self.alphabet.len();
+        self.core
+            .set_segment(position as u8)
+            .expect("segment of rotor");
     }
 
     pub fn segment(self: &Self) -> char {
-        return SEGMENTS.chars().nth(self.position).unwrap();
+        return self.alphabet.letter(self.position());
     }
 
     pub fn set_segment(self: &mut Self, pos: char) -> Result<char, RotorError> {
-        let val = pos.to_ascii_uppercase();
-
-        self.position = SEGMENTS
-            .find(val)
+        let index = self
+            .alphabet
+            .index(pos)
             .ok_or(RotorError::InvalidSegmentPosition(pos))?;
+        self.core
+            .set_segment(index as u8)
+            .expect("segment of rotor");
 
-        return Ok(val);
+        return Ok(self.segment());
     }
 
     pub fn ring_offset(&self) -> char {
-        return SEGMENTS.chars().nth(self.ring_offset).unwrap();
+        return self.alphabet.letter(self.core.ring_segment() as usize);
     }
 
     pub fn set_ring_offset(self: &mut Self, pos: char) -> Result<char, RotorError> {
-        let val = pos.to_ascii_uppercase();
-
-        self.ring_offset = SEGMENTS
-            .find(val)
+        let index = self
+            .alphabet
+            .index(pos)
             .ok_or(RotorError::InvalidRingOffset(pos))?;
+        self.core
+            .set_ring_segment(index as u8)
+            .expect("segment of rotor");
 
-        return Ok(val);
+        return Ok(self.ring_offset());
     }
 
     // return true if at turnover notch
     pub fn at_notch(self: &Self) -> bool {
-        if self
+        return self.core.at_notch();
+    }
+
+    // indexes of segments with notch, sorted
+    pub fn notch_positions(self: &Self) -> Vec<usize> {
+        let mut positions: Vec<usize> = self
             .notches
-            .contains(SEGMENTS.chars().nth(self.position).unwrap())
-        {
-            return true;
+            .chars()
+            .map(|notch| self.alphabet.index(notch).unwrap())
+            .collect();
+        positions.sort();
+        positions.dedup();
+        return positions;
+    }
+
+    // return count of advances before the rotor gets to a turnover notch, None if
+    // rotor has no notches
+    pub fn steps_to_notch(self: &Self) -> Option<usize> {
+        let size = self.alphabet.len();
+        let position = self.position();
+        let mut retval: Option<usize> = None;
+        for notch in self.notch_positions() {
+            let steps = (notch + size - position) % size;
+            retval = Some(retval.map_or(steps, |val| val.min(steps)));
         }
-        return false;
+        return retval;
     }
 
     // return true if need turnover next rotor (was at notch position)
     pub fn advance(self: &mut Self) -> bool {
-        let need_turnover = self.at_notch();
-        if self.position == SEGMENTS.len() - 1 {
-            self.position = 0;
-        } else {
-            self.position += 1;
-        }
-        return need_turnover;
+        return self.core.advance();
     }
 
-    pub fn forward(self: &Self, mut ch: char) -> Result<char, RotorError> {
-        ch.make_ascii_uppercase();
+    // advance the rotor several times without turnover of other rotors
+    pub fn advance_by(self: &mut Self, steps: usize) {
+        self.core.advance_by(steps);
+    }
 
-        let segment = SEGMENTS
-            .find(ch)
+    pub fn forward(self: &Self, ch: char) -> Result<char, RotorError> {
+        let segment = self
+            .alphabet
+            .index(ch)
             .ok_or(RotorError::InvalidSegmentPosition(ch))?;
 
-        let correction = self.position as isize - self.ring_offset as isize;
-
-        // find firing segment index
-        let mut index = correction + segment as isize;
-        if index < 0 {
-            index = SEGMENTS.len() as isize + index;
-        } else if index as usize >= SEGMENTS.len() {
-            index = (index as usize % SEGMENTS.len()) as isize;
-        }
-
-        // get output segment
-        let val = self.ring.chars().nth(index as usize).unwrap();
-
-        // apply correction for output segment index
-        let mut index = SEGMENTS.find(val).unwrap() as isize - correction as isize;
-        if index < 0 {
-            index = SEGMENTS.len() as isize + index;
-        } else if index as usize >= SEGMENTS.len() {
-            index = (index as usize % SEGMENTS.len()) as isize;
-        }
-
-        return Ok(SEGMENTS.chars().nth(index as usize).unwrap());
+        let output = self.core.forward(segment as u8);
+        return Ok(self.alphabet.letter(output as usize));
     }
 
-    pub fn backward(self: &Self, mut ch: char) -> Result<char, RotorError> {
-        ch.make_ascii_uppercase();
-
-        let segment = SEGMENTS
-            .find(ch)
+    pub fn backward(self: &Self, ch: char) -> Result<char, RotorError> {
+        let segment = self
+            .alphabet
+            .index(ch)
             .ok_or(RotorError::InvalidSegmentPosition(ch))?;
 
-        let correction = self.position as isize - self.ring_offset as isize;
-
-        let mut index = correction + segment as isize;
-        if index < 0 {
-            index = SEGMENTS.len() as isize + index;
-        } else if index as usize >= SEGMENTS.len() {
-            index = (index as usize % SEGMENTS.len()) as isize;
-        }
+        let output = self.core.backward(segment as u8);
+        return Ok(self.alphabet.letter(output as usize));
+    }
+}
 
-        let val = SEGMENTS.chars().nth(index as usize).unwrap();
+impl Wheel for Rotor {
+    fn at_notch(self: &Self) -> bool {
+        return self.core.at_notch();
+    }
 
-        let mut index = self.ring.find(val).unwrap() as isize - correction as isize;
-        if index < 0 {
-            index = SEGMENTS.len() as isize + index;
-        } else if index as usize >= SEGMENTS.len() {
-            index = (index as usize % SEGMENTS.len()) as isize;
-        }
+    fn at_start(self: &Self) -> bool {
+        return self.position() == 0;
+    }
 
-        return Ok(SEGMENTS.chars().nth(index as usize).unwrap());
+    fn turn(self: &mut Self) {
+        self.core.advance();
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::device::details::RING_I;
+    use crate::device::details::SEGMENTS;
     use crate::device::rotor::Rotor;
-    use crate::device::rotor::SEGMENTS;
+    use proptest::prelude::*;
 
     #[test]
     fn new_rotors() {
@@ -225,6 +291,44 @@ mod tests {
         Rotor::model("VIII").unwrap();
     }
 
+    #[test]
+    fn reversed_rotors() {
+        let rotor = Rotor::model("II'").unwrap();
+        assert!(rotor.is_reversed());
+        assert!(Rotor::model("II").unwrap().is_reversed() == false);
+        assert!(Rotor::model("IX'").is_err());
+
+        // reversed twice is original rotor
+        let mut twice = rotor.reversed();
+        let mut control = Rotor::model("II").unwrap();
+        assert!(twice.is_reversed() == false);
+        for _ in 0..SEGMENTS.len() {
+            twice.advance();
+            control.advance();
+            for ch in SEGMENTS.chars() {
+                assert_eq!(twice.forward(ch).unwrap(), control.forward(ch).unwrap());
+                assert_eq!(twice.at_notch(), control.at_notch());
+            }
+        }
+
+        // signal goes through mirrored contacts in opposite direction
+        let mirror = |ch: char| {
+            let index = SEGMENTS.find(ch).unwrap();
+            SEGMENTS.chars().nth((26 - index) % 26).unwrap()
+        };
+        for ch in SEGMENTS.chars() {
+            assert_eq!(
+                rotor.forward(ch).unwrap(),
+                mirror(control.backward(mirror(ch)).unwrap())
+            );
+        }
+
+        // notch E is mirrored to W
+        let mut rotor = Rotor::model("II'").unwrap();
+        rotor.set_segment('W').unwrap();
+        assert!(rotor.at_notch());
+    }
+
     #[test]
     fn set_active_segment() {
         let mut rotor = Rotor::model("I").unwrap();
@@ -256,7 +360,8 @@ mod tests {
 
     #[test]
     fn encryption() {
-        let rotors = vec![
+        let rotors = [
+            Rotor::model("I'").unwrap(),
             Rotor::model("I").unwrap(),
             Rotor::model("II").unwrap(),
             Rotor::model("III").unwrap(),
@@ -271,7 +376,7 @@ mod tests {
             for _ in 0..SEGMENTS.len() {
                 rotor.advance();
                 for ch in SEGMENTS.chars() {
-                    for input in vec![ch, ch.to_ascii_lowercase()] {
+                    for input in [ch, ch.to_ascii_lowercase()] {
                         let intermediate = rotor.forward(input).unwrap();
                         let output = rotor.backward(intermediate).unwrap();
 
@@ -291,9 +396,40 @@ mod tests {
         Rotor::model("I").unwrap().forward('.').unwrap_err();
     }
 
+    #[test]
+    fn steps_to_notch() {
+        let mut rotor = Rotor::model("VI").unwrap();
+        assert_eq!(rotor.steps_to_notch(), Some(12));
+        rotor.set_segment('M').unwrap();
+        assert_eq!(rotor.steps_to_notch(), Some(0));
+        rotor.advance_by(27);
+        assert_eq!(rotor.segment(), 'N');
+        assert_eq!(rotor.steps_to_notch(), Some(12));
+
+        let rotor = Rotor::new(RING_I, "").unwrap();
+        assert_eq!(rotor.steps_to_notch(), None);
+    }
+
+    #[test]
+    fn thin() {
+        assert!(Rotor::model("beta").unwrap().is_thin());
+        assert!(Rotor::model("GAMMA").unwrap().is_thin());
+        assert!(Rotor::model("I").unwrap().is_thin() == false);
+    }
+
+    #[test]
+    fn notch_positions() {
+        assert_eq!(Rotor::model("VI").unwrap().notch_positions(), [12, 25]);
+        assert_eq!(
+            Rotor::new(RING_I, "QAQ").unwrap().notch_positions(),
+            [0, 16]
+        );
+        assert!(Rotor::new(RING_I, "").unwrap().notch_positions().is_empty());
+    }
+
     #[test]
     fn position_and_offset_are_equivalents() {
-        let mut rotors = vec![
+        let mut rotors = [
             Rotor::model("I").unwrap(),
             Rotor::model("II").unwrap(),
             Rotor::model("III").unwrap(),
@@ -303,7 +439,7 @@ mod tests {
             Rotor::model("VII").unwrap(),
             Rotor::model("VIII").unwrap(),
         ];
-        let mut controls = vec![
+        let mut controls = [
             Rotor::model("I").unwrap(),
             Rotor::model("II").unwrap(),
             Rotor::model("III").unwrap(),
@@ -336,4 +472,29 @@ mod tests {
             }
         }
     }
+
+    proptest! {
+        #[test]
+        fn forward_and_backward_are_inverse(
+            wiring in Just(SEGMENTS.chars().collect::<Vec<char>>()).prop_shuffle(),
+            notches in proptest::sample::subsequence(SEGMENTS.chars().collect::<Vec<char>>(), 0..4),
+            position in 0_usize..26,
+            offset in proptest::sample::select(SEGMENTS.chars().collect::<Vec<char>>()),
+            reversed in any::<bool>(),
+        ) {
+            let wiring: String = wiring.into_iter().collect();
+            let notches: String = notches.into_iter().collect();
+            let mut rotor = Rotor::new(&wiring, &notches).unwrap();
+            if reversed {
+                rotor = rotor.reversed();
+            }
+            rotor.set_position(position);
+            rotor.set_ring_offset(offset).unwrap();
+
+            for ch in SEGMENTS.chars() {
+                prop_assert_eq!(rotor.backward(rotor.forward(ch).unwrap()).unwrap(), ch);
+                prop_assert_eq!(rotor.forward(rotor.backward(ch).unwrap()).unwrap(), ch);
+            }
+        }
+    }
 }
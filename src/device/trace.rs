@@ -0,0 +1,57 @@
+use std::fmt;
+
+// Stages of the signal path, rotors are numbered from left to right starting
+// from 1, like in key sheets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Plugboard,
+    Rotor(usize),
+    Reflector,
+    RotorBack(usize),
+    PlugboardBack,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stage::Plugboard => write!(f, "plugboard"),
+            Stage::Rotor(slot) => write!(f, "rotor {}", slot),
+            Stage::Reflector => write!(f, "reflector"),
+            Stage::RotorBack(slot) => write!(f, "rotor {} back", slot),
+            Stage::PlugboardBack => write!(f, "plugboard back"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    pub stage: Stage,
+    pub input: char,
+    pub output: char,
+}
+
+// path of one key press, positions are taken after stepping, when the signal
+// goes through the rotors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    pub input: char,
+    pub output: char,
+    pub positions: String,
+    pub steps: Vec<TraceStep>,
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} -> {} at {}", self.input, self.output, self.positions)?;
+        for step in self.steps.iter() {
+            write!(
+                f,
+                "\n  {:<16}{} -> {}",
+                step.stage.to_string(),
+                step.input,
+                step.output
+            )?;
+        }
+        Ok(())
+    }
+}
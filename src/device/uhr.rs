@@ -0,0 +1,230 @@
+use crate::device::alphabet::Alphabet;
+use crate::device::plugboard::PlugBoard;
+use crate::device::plugboard::PlugBoardError;
+use std::error;
+use std::fmt;
+
+pub const UHR_CABLES: usize = 10;
+pub const UHR_POSITIONS: usize = 40;
+
+// Wiring of the Uhr disc: contact on A side -> contact on B side at dial 00
+pub const UHR_WIRING: [usize; UHR_POSITIONS] = [
+    6, 31, 4, 29, 18, 39, 16, 25, 30, 23, 28, 1, 38, 11, 36, 37, 26, 27, 24, 21, 14, 3, 12, 17, 2,
+    7, 0, 33, 10, 35, 8, 5, 22, 19, 20, 13, 34, 15, 32, 9,
+];
+
+// Contacts of the large pins of B plugs 1b - 10b. Small pin is next but one
+// contact
+const UHR_B_PLUGS: [usize; UHR_CABLES] = [4, 16, 28, 36, 24, 12, 0, 8, 20, 32];
+
+// Uhr box of the Luftwaffe. Ten cables go from the Steckerbrett to the box:
+// first letters of key sheet pairs get A plugs (1a - 10a), second letters get
+// B plugs (1b - 10b). Every plug has large and small pin, and the disc of the
+// box connects large pins of A plugs with small pins of B plugs and vice versa,
+// rotated by dial setting 00 - 39. So connections are not reciprocal, except
+// of dial settings that are multiple of 4, where setting 00 is same as usual
+// plugboard with the key sheet pairs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Uhr {
+    alphabet: Alphabet,
+    a_plugs: Vec<char>,
+    b_plugs: Vec<char>,
+    dial: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum UhrError {
+    InvalidCableCount(/*actual*/ usize, /*expected*/ usize),
+    InvalidDial(/*dial*/ usize),
+    InvalidCables(PlugBoardError),
+}
+
+impl fmt::Display for UhrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UhrError::InvalidCableCount(actual, expected) => write!(
+                f,
+                "invalid count of Uhr cables: {}/{} (actual/expected)",
+                actual, expected
+            ),
+            UhrError::InvalidDial(dial) => {
+                write!(f, "invalid Uhr dial: {}, expected 00 - 39", dial)
+            }
+            UhrError::InvalidCables(err) => write!(f, "invalid Uhr cables: {}", err),
+        }
+    }
+}
+
+impl error::Error for UhrError {}
+
+impl Uhr {
+    // cables in key sheet notation, like "AT BL DF GJ HM NW OP QY RZ VX"
+    pub fn new(cables: &str, dial: usize) -> Result<Self, UhrError> {
+        return Self::with_alphabet(&Alphabet::latin(), cables, dial);
+    }
+
+    pub fn with_alphabet(alphabet: &Alphabet, cables: &str, dial: usize) -> Result<Self, UhrError> {
+        let pairs = PlugBoard::with_alphabet(alphabet)
+            .parse_cables(cables, None)
+            .map_err(UhrError::InvalidCables)?;
+        if pairs.len() != UHR_CABLES {
+            return Err(UhrError::InvalidCableCount(pairs.len(), UHR_CABLES));
+        }
+
+        let mut uhr = Self {
+            alphabet: alphabet.clone(),
+            a_plugs: pairs.iter().map(|(a, _)| *a).collect(),
+            b_plugs: pairs.iter().map(|(_, b)| *b).collect(),
+            dial: 0,
+        };
+        uhr.set_dial(dial)?;
+
+        return Ok(uhr);
+    }
+
+    pub fn dial(&self) -> usize {
+        return self.dial;
+    }
+
+    pub fn set_dial(&mut self, dial: usize) -> Result<usize, UhrError> {
+        if dial >= UHR_POSITIONS {
+            return Err(UhrError::InvalidDial(dial));
+        }
+        self.dial = dial;
+        return Ok(dial);
+    }
+
+    // cables in key sheet notation, A plug first
+    pub fn cables(&self) -> String {
+        return self
+            .a_plugs
+            .iter()
+            .zip(self.b_plugs.iter())
+            .map(|(a, b)| format!("{}{}", a, b))
+            .collect::<Vec<String>>()
+            .join(" ");
+    }
+
+    // from A side contact to B side contact through rotated disc
+    fn disc(&self, contact: usize) -> usize {
+        let out = UHR_WIRING[(contact + self.dial) % UHR_POSITIONS];
+        return (out + UHR_POSITIONS - self.dial) % UHR_POSITIONS;
+    }
+
+    // from B side contact to A side contact through rotated disc
+    fn disc_back(&self, contact: usize) -> usize {
+        let val = (contact + self.dial) % UHR_POSITIONS;
+        let out = UHR_WIRING.iter().position(|out| *out == val).unwrap();
+        return (out + UHR_POSITIONS - self.dial) % UHR_POSITIONS;
+    }
+
+    // from keyboard to rotors
+    pub fn forward(&self, ch: char) -> Result<char, PlugBoardError> {
+        let val = self
+            .alphabet
+            .normalize(ch)
+            .ok_or(PlugBoardError::InvalidCharacter(ch))?;
+
+        if let Some(plug) = self.a_plugs.iter().position(|a| *a == val) {
+            // large pin of A plug -> small pin of B plug
+            let contact = self.disc(plug * 4);
+            let plug = UHR_B_PLUGS
+                .iter()
+                .position(|large| large + 2 == contact)
+                .expect("disc connects A plugs with B plugs");
+            return Ok(self.b_plugs[plug]);
+        }
+
+        if let Some(plug) = self.b_plugs.iter().position(|b| *b == val) {
+            // large pin of B plug -> small pin of A plug
+            let contact = self.disc_back(UHR_B_PLUGS[plug]);
+            return Ok(self.a_plugs[(contact - 2) / 4]);
+        }
+
+        return Ok(val);
+    }
+
+    // from rotors to lamps, inverse of forward
+    pub fn backward(&self, ch: char) -> Result<char, PlugBoardError> {
+        let val = self
+            .alphabet
+            .normalize(ch)
+            .ok_or(PlugBoardError::InvalidCharacter(ch))?;
+
+        for plug in self.a_plugs.iter().chain(self.b_plugs.iter()) {
+            if self.forward(*plug)? == val {
+                return Ok(*plug);
+            }
+        }
+
+        return Ok(val);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device::details::ALPHABET;
+    use crate::device::plugboard::PlugBoard;
+    use crate::device::uhr::Uhr;
+
+    const CABLES: &str = "AT BL DF GJ HM NW OP QY RZ VX";
+
+    #[test]
+    fn new_uhr() {
+        let uhr = Uhr::new(CABLES, 27).unwrap();
+        assert_eq!(uhr.dial(), 27);
+        assert_eq!(uhr.cables(), CABLES);
+
+        assert!(Uhr::new(CABLES, 40).is_err());
+        assert!(Uhr::new("AT BL DF", 0).is_err());
+        assert!(Uhr::new("AT BL DF GJ HM NW OP QY RZ VA", 0).is_err());
+    }
+
+    #[test]
+    fn dial_00_is_plugboard() {
+        let uhr = Uhr::new(CABLES, 0).unwrap();
+        let mut board = PlugBoard::new();
+        board.set_cables(CABLES, None).unwrap();
+
+        for ch in ALPHABET.chars() {
+            assert_eq!(uhr.forward(ch).unwrap(), board.crypt(ch).unwrap());
+        }
+    }
+
+    #[test]
+    fn connections() {
+        for dial in 0..40 {
+            let uhr = Uhr::new(CABLES, dial).unwrap();
+
+            let mut outputs = String::new();
+            let mut reciprocal = true;
+            for ch in ALPHABET.chars() {
+                let out = uhr.forward(ch).unwrap();
+                assert_eq!(uhr.backward(out).unwrap(), ch);
+                if uhr.forward(out).unwrap() != ch {
+                    reciprocal = false;
+                }
+
+                // plugged letters are always connected to other side
+                if "ABDGHNOQRV".contains(ch) {
+                    assert!("TLFJMWPYZX".contains(out));
+                } else if "TLFJMWPYZX".contains(ch) == false {
+                    assert_eq!(out, ch);
+                }
+                outputs.push(out);
+            }
+
+            assert!(ALPHABET.chars().all(|ch| outputs.contains(ch)));
+            assert_eq!(reciprocal, dial % 4 == 0, "dial: {}", dial);
+        }
+    }
+
+    #[test]
+    fn dial_01() {
+        // 1a -> 3b, 2a -> 4b, 3a -> 9b, ...
+        let uhr = Uhr::new(CABLES, 1).unwrap();
+        assert_eq!(uhr.forward('A').unwrap(), 'F');
+        assert_eq!(uhr.forward('B').unwrap(), 'J');
+        assert_eq!(uhr.forward('D').unwrap(), 'Z');
+    }
+}
@@ -0,0 +1,363 @@
+// C API over Device, see include/enigma.h generated from this file.
+//
+// Device is used through opaque handle created by `enigma_device_new` and
+// released by `enigma_device_free`. Every function returns status code, and
+// message of the last error of the calling thread is available by
+// `enigma_last_error`. Pointers must be null or valid for the sizes given
+// with them, strings are NUL-terminated UTF-8.
+#![allow(clippy::missing_safety_doc)]
+
+use crate::device::key::Key;
+use crate::device::Device;
+use std::cell::RefCell;
+use std::ffi::c_char;
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::ptr;
+use std::slice;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnigmaStatus {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidUtf8 = 2,
+    InvalidSettings = 3,
+    BufferTooSmall = 4,
+    Panic = 5,
+}
+
+// opaque for C
+pub struct EnigmaDevice {
+    device: Device,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(message: &str) {
+    // message can't have NUL inside, it is formatted by Display of errors
+    let message = CString::new(message.replace('\0', " ")).expect("no NUL");
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+fn fail(status: EnigmaStatus, message: &str) -> EnigmaStatus {
+    set_last_error(message);
+    return status;
+}
+
+// panics must not unwind into C. Error of previous call is cleared, so
+// enigma_last_error is empty after success
+fn guard<F: FnOnce() -> EnigmaStatus>(f: F) -> EnigmaStatus {
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::default());
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(status) => return status,
+        Err(_) => return fail(EnigmaStatus::Panic, "internal error"),
+    }
+}
+
+unsafe fn to_str<'a>(s: *const c_char) -> Result<&'a str, EnigmaStatus> {
+    if s.is_null() {
+        return Err(fail(EnigmaStatus::NullPointer, "string is null"));
+    }
+    return CStr::from_ptr(s)
+        .to_str()
+        .map_err(|err| fail(EnigmaStatus::InvalidUtf8, &err.to_string()));
+}
+
+unsafe fn to_device<'a>(device: *mut EnigmaDevice) -> Result<&'a mut Device, EnigmaStatus> {
+    match device.as_mut() {
+        Some(handle) => return Ok(&mut handle.device),
+        None => return Err(fail(EnigmaStatus::NullPointer, "device is null")),
+    }
+}
+
+// copies string with NUL into the buffer, `written` gets length of the
+// string without NUL. If the buffer is null or too small `written` gets
+// required size of the buffer with NUL, so null buffer can be used to query
+// the size
+unsafe fn write_string(
+    s: &str,
+    buffer: *mut c_char,
+    capacity: usize,
+    written: *mut usize,
+) -> EnigmaStatus {
+    if written.is_null() == false {
+        *written = s.len() + 1;
+    }
+    if buffer.is_null() {
+        return fail(EnigmaStatus::NullPointer, "buffer is null");
+    }
+    if s.len() + 1 > capacity {
+        return fail(
+            EnigmaStatus::BufferTooSmall,
+            &format!(
+                "buffer too small: {}/{} (actual/required)",
+                capacity,
+                s.len() + 1
+            ),
+        );
+    }
+
+    ptr::copy_nonoverlapping(s.as_ptr(), buffer as *mut u8, s.len());
+    *buffer.add(s.len()) = 0;
+    if written.is_null() == false {
+        *written = s.len();
+    }
+    return EnigmaStatus::Ok;
+}
+
+// Enigma I with rotors I II III and reflector B, positions AAA, no cables
+#[no_mangle]
+pub extern "C" fn enigma_device_new() -> *mut EnigmaDevice {
+    let mut device = Device::new();
+    device
+        .set_reflector_type("B")
+        .expect("historical reflector");
+    for rotor in ["I", "II", "III"] {
+        device.add_rotor_type(rotor).expect("historical rotor");
+    }
+    return Box::into_raw(Box::new(EnigmaDevice { device }));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn enigma_device_free(device: *mut EnigmaDevice) {
+    if device.is_null() == false {
+        drop(Box::from_raw(device));
+    }
+}
+
+// settings in key format, like
+// "enigma-i | B | II IV V | BUL | BLA | AV BS CG DL FU HZ IN KM OW RX".
+// Device is not changed on error
+#[no_mangle]
+pub unsafe extern "C" fn enigma_device_configure(
+    device: *mut EnigmaDevice,
+    settings: *const c_char,
+) -> EnigmaStatus {
+    return guard(|| {
+        let device = match to_device(device) {
+            Ok(device) => device,
+            Err(status) => return status,
+        };
+        let settings = match to_str(settings) {
+            Ok(settings) => settings,
+            Err(status) => return status,
+        };
+
+        match settings.parse::<Key>().and_then(|key| key.device()) {
+            Ok(configured) => *device = configured,
+            Err(err) => return fail(EnigmaStatus::InvalidSettings, &err.to_string()),
+        }
+        return EnigmaStatus::Ok;
+    });
+}
+
+// Encrypts `length` bytes of UTF-8 input. Characters not in the alphabet are
+// skipped. Output is NUL-terminated, `written` gets its length without NUL.
+// If the output buffer is null or too small the device is not moved and
+// `written` gets required capacity with NUL
+#[no_mangle]
+pub unsafe extern "C" fn enigma_device_crypt(
+    device: *mut EnigmaDevice,
+    input: *const c_char,
+    length: usize,
+    output: *mut c_char,
+    capacity: usize,
+    written: *mut usize,
+) -> EnigmaStatus {
+    return guard(|| {
+        let device = match to_device(device) {
+            Ok(device) => device,
+            Err(status) => return status,
+        };
+        if input.is_null() && length > 0 {
+            return fail(EnigmaStatus::NullPointer, "input is null");
+        }
+        let bytes = match length {
+            0 => &[],
+            _ => slice::from_raw_parts(input as *const u8, length),
+        };
+        let input = match std::str::from_utf8(bytes) {
+            Ok(input) => input,
+            Err(err) => return fail(EnigmaStatus::InvalidUtf8, &err.to_string()),
+        };
+
+        let mut moved = device.clone();
+        let text: String = input
+            .chars()
+            .filter_map(|ch| moved.crypt(ch).ok())
+            .collect();
+
+        let status = write_string(&text, output, capacity, written);
+        if status == EnigmaStatus::Ok {
+            *device = moved;
+        }
+        return status;
+    });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn enigma_device_positions(
+    device: *mut EnigmaDevice,
+    buffer: *mut c_char,
+    capacity: usize,
+) -> EnigmaStatus {
+    return guard(|| {
+        let device = match to_device(device) {
+            Ok(device) => device,
+            Err(status) => return status,
+        };
+        return write_string(&device.segments(), buffer, capacity, ptr::null_mut());
+    });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn enigma_device_set_positions(
+    device: *mut EnigmaDevice,
+    positions: *const c_char,
+) -> EnigmaStatus {
+    return guard(|| {
+        let device = match to_device(device) {
+            Ok(device) => device,
+            Err(status) => return status,
+        };
+        let positions = match to_str(positions) {
+            Ok(positions) => positions,
+            Err(status) => return status,
+        };
+
+        if let Err(err) = device.set_segments(positions) {
+            return fail(EnigmaStatus::InvalidSettings, &err.to_string());
+        }
+        return EnigmaStatus::Ok;
+    });
+}
+
+// message of the last error of the calling thread, empty if there was no
+// error. Pointer is valid until the next call of API in the thread
+#[no_mangle]
+pub extern "C" fn enigma_last_error() -> *const c_char {
+    return LAST_ERROR.with(|last| last.borrow().as_ptr());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ffi::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn crypt() {
+        unsafe {
+            let device = enigma_device_new();
+            let settings =
+                c"enigma-i | B | II IV V | BUL | BLA | AV BS CG DL FU HZ IN KM OW RX".as_ptr();
+            assert_eq!(enigma_device_configure(device, settings), EnigmaStatus::Ok);
+
+            let input = "EDPUD NRGYS";
+            let mut output = [0 as c_char; 16];
+            let mut written = 0;
+            let status = enigma_device_crypt(
+                device,
+                input.as_ptr() as *const c_char,
+                input.len(),
+                output.as_mut_ptr(),
+                output.len(),
+                &mut written,
+            );
+            assert_eq!(status, EnigmaStatus::Ok);
+            assert_eq!(written, 10);
+            assert_eq!(
+                CStr::from_ptr(output.as_ptr()).to_str().unwrap(),
+                "AUFKLXABTE"
+            );
+
+            // device is not moved if output doesn't fit
+            let status = enigma_device_crypt(
+                device,
+                input.as_ptr() as *const c_char,
+                input.len(),
+                output.as_mut_ptr(),
+                4,
+                &mut written,
+            );
+            assert_eq!(status, EnigmaStatus::BufferTooSmall);
+            assert_eq!(written, 11);
+
+            // null buffer gives same required capacity
+            let status = enigma_device_crypt(
+                device,
+                input.as_ptr() as *const c_char,
+                input.len(),
+                ptr::null_mut(),
+                0,
+                &mut written,
+            );
+            assert_eq!(status, EnigmaStatus::NullPointer);
+            assert_eq!(written, 11);
+            let status = enigma_device_crypt(
+                device,
+                input.as_ptr() as *const c_char,
+                input.len(),
+                output.as_mut_ptr(),
+                written,
+                &mut written,
+            );
+            assert_eq!(status, EnigmaStatus::Ok);
+            assert_eq!(written, 10);
+            assert!(CStr::from_ptr(enigma_last_error()).to_bytes().is_empty());
+
+            assert_eq!(
+                enigma_device_set_positions(device, c"BLA".as_ptr()),
+                EnigmaStatus::Ok
+            );
+            enigma_device_positions(device, output.as_mut_ptr(), output.len());
+            assert_eq!(CStr::from_ptr(output.as_ptr()).to_str().unwrap(), "BLA");
+
+            assert_eq!(
+                enigma_device_set_positions(device, c"BL1".as_ptr()),
+                EnigmaStatus::InvalidSettings
+            );
+            assert!(CStr::from_ptr(enigma_last_error()).to_bytes().is_empty() == false);
+
+            // error is cleared by next successful call
+            assert_eq!(
+                enigma_device_set_positions(device, c"BLA".as_ptr()),
+                EnigmaStatus::Ok
+            );
+            assert!(CStr::from_ptr(enigma_last_error()).to_bytes().is_empty());
+
+            enigma_device_free(device);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        unsafe {
+            assert_eq!(
+                enigma_device_configure(ptr::null_mut(), c"".as_ptr()),
+                EnigmaStatus::NullPointer
+            );
+
+            let device = enigma_device_new();
+            assert_eq!(
+                enigma_device_configure(device, c"m3 | B | I II".as_ptr()),
+                EnigmaStatus::InvalidSettings
+            );
+            assert_eq!(
+                enigma_device_configure(device, ptr::null()),
+                EnigmaStatus::NullPointer
+            );
+            let invalid = [0xff_u8, 0];
+            assert_eq!(
+                enigma_device_set_positions(device, invalid.as_ptr() as *const c_char),
+                EnigmaStatus::InvalidUtf8
+            );
+            enigma_device_free(device);
+            enigma_device_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,25 @@
+#![allow(
+    clippy::bool_comparison,
+    clippy::needless_arbitrary_self_type,
+    clippy::needless_return,
+    clippy::new_without_default,
+    clippy::single_match
+)]
+
+// machine without std and allocations, parts of `device` are built on it
+pub use enigma_core;
+
+#[cfg(feature = "std")]
+pub mod cipher;
+#[cfg(feature = "std")]
+pub mod device;
+#[cfg(feature = "std")]
+pub mod ffi;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "std")]
+pub mod server;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod typex;
@@ -1,52 +1,400 @@
-pub mod device;
+#![allow(
+    clippy::bool_comparison,
+    clippy::needless_arbitrary_self_type,
+    clippy::needless_return,
+    clippy::new_without_default,
+    clippy::single_match
+)]
 
-use crate::device::Device;
-use clap::{App, Arg};
+use clap::{App, Arg, ArgMatches};
+use enigma::cipher;
+use enigma::cipher::Cipher;
+use enigma::device::alphabet::Alphabet;
+use enigma::device::batch::Batch;
+use enigma::device::block::BlockError;
+use enigma::device::block::Notation;
+use enigma::device::block::Stepping;
+use enigma::device::builder::Machine;
+use enigma::device::catalog::Catalog;
+use enigma::device::details::ALPHABET;
+use enigma::device::key::Key;
+use enigma::device::rotor::Rotor;
+use enigma::device::Device;
+use enigma::server::Server;
+use enigma::stats;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Serialize;
 use std::error;
+use std::fs;
 use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+
+// Kind of error is reported to scripts as exit code and in `--format json`
+// error objects
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum FailureKind {
+    Runtime,
+    Configuration,
+    Input,
+}
+
+impl FailureKind {
+    fn exit_code(&self) -> i32 {
+        match self {
+            FailureKind::Runtime => 1,
+            FailureKind::Configuration => 2,
+            FailureKind::Input => 3,
+        }
+    }
+}
+
+struct Failure {
+    kind: FailureKind,
+    error: Box<dyn error::Error>,
+}
+
+impl Failure {
+    fn configuration(error: impl Into<Box<dyn error::Error>>) -> Self {
+        return Self {
+            kind: FailureKind::Configuration,
+            error: error.into(),
+        };
+    }
+
+    fn input(error: impl Into<Box<dyn error::Error>>) -> Self {
+        return Self {
+            kind: FailureKind::Input,
+            error: error.into(),
+        };
+    }
+}
+
+impl<E: Into<Box<dyn error::Error>>> From<E> for Failure {
+    fn from(error: E) -> Self {
+        return Self {
+            kind: FailureKind::Runtime,
+            error: error.into(),
+        };
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorReport {
+    kind: FailureKind,
+    message: String,
+}
+
+// result of encryption for `--format json`
+#[derive(Serialize)]
+struct CryptReport {
+    output: String,
+    start: String,
+    end: String,
+    ring_offsets: String,
+    plugboard: String,
+    uhr: Option<usize>,
+    skipped: Vec<Skipped>,
+}
+
+// character of input that is not in the alphabet, offset is index of the
+// character (not byte) in the input
+#[derive(Serialize)]
+struct Skipped {
+    offset: usize,
+    character: char,
+}
+
+fn main() {
+    let input = Arg::with_name("input")
+        .help("input file, stdin if not set or -")
+        .index(1);
 
-fn main() -> Result<(), Box<dyn error::Error>> {
     let matches = App::new("Enigma")
-        .about("Enigma simulation")
-        .arg(
-            Arg::with_name("plug-pairs")
-                .short('p')
-                .long("plug-pairs")
-                .help("plug pairs, like \"ABCD\"")
-                .default_value(""),
+        .about("Enigma simulation. Without command encrypts stdin line by line")
+        .args(machine_args())
+        .subcommand(
+            App::new("encrypt")
+                .about("encrypt file or stdin")
+                .arg(input.clone())
+                .arg(
+                    Arg::with_name("groups")
+                        .long("groups")
+                        .help("size of letter groups in output, 0 for no groups")
+                        .default_value("5"),
+                ),
+        )
+        .subcommand(
+            App::new("decrypt")
+                .about("decrypt file or stdin")
+                .arg(input.clone())
+                .arg(
+                    Arg::with_name("groups")
+                        .long("groups")
+                        .help("size of letter groups in output, 0 for no groups")
+                        .default_value("0"),
+                ),
+        )
+        .subcommand(
+            App::new("trace")
+                .about("print signal path of every letter")
+                .arg(
+                    Arg::with_name("text")
+                        .help("letters to trace, stdin if not set")
+                        .index(1),
+                ),
         )
-        .arg(
-            Arg::with_name("reflector")
-                .short('f')
-                .long("reflector")
-                .help("reflector (A-C)")
-                .default_value("B"),
+        .subcommand(
+            App::new("analyze")
+                .about("statistics of the text and attacks on the machine")
+                .arg(input.clone())
+                .arg(
+                    Arg::with_name("period")
+                        .long("period")
+                        .takes_value(false)
+                        .help("print cycles of rotor positions instead of text statistics"),
+                )
+                .arg(
+                    Arg::with_name("attack")
+                        .long("attack")
+                        .takes_value(false)
+                        .help("find start positions of the rotors by index of coincidence"),
+                )
+                .arg(
+                    Arg::with_name("top")
+                        .long("top")
+                        .help("count of reported start positions")
+                        .default_value("5"),
+                ),
         )
-        .arg(
-            Arg::with_name("rotor")
-                .short('r')
-                .long("rotor")
-                .value_delimiter(',')
-                .help("rotors for usage (I - VIII)")
-                .default_values(&["I", "II", "III"]),
+        .subcommand(
+            App::new("keygen")
+                .about("generate random keys, one key per line")
+                .arg(
+                    Arg::with_name("machine")
+                        .long("machine")
+                        .help("machine type (enigma-i, m3, m4)")
+                        .default_value("enigma-i"),
+                )
+                .arg(
+                    Arg::with_name("days")
+                        .long("days")
+                        .help("count of keys, like 31 for monthly key sheet")
+                        .default_value("1"),
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .long("seed")
+                        .takes_value(true)
+                        .help("seed of random generator, for same keys every time"),
+                ),
         )
-        .arg(
-            Arg::with_name("segments")
-                .short('s')
-                .long("segments")
-                .value_delimiter(',')
-                .help("rotor settings, like: \"ABC\"")
-                .default_value(""),
+        .subcommand(
+            App::new("batch")
+                .about("decrypt many messages with own message keys. Input has one JSON record per line: {\"id\": \"1\", \"indicator\": \"KCH\", \"text\": \"EDPUD\"}, indicator is encrypted at --segments, or \"positions\" are set directly")
+                .arg(input.clone()),
         )
-        .arg(
-            Arg::with_name("ring-offsets")
-                .short('o')
-                .long("ring-offsets")
-                .help("ring offsets, like \"ABC\"")
-                .default_value(""),
+        .subcommand(
+            App::new("serve")
+                .about("serve sessions of machines by line-delimited JSON protocol, machine settings are taken from requests")
+                .arg(
+                    Arg::with_name("tcp")
+                        .long("tcp")
+                        .takes_value(true)
+                        .conflicts_with("unix")
+                        .help("TCP address to listen, like 127.0.0.1:7941. Port 0 picks free port"),
+                )
+                .arg(
+                    Arg::with_name("unix")
+                        .long("unix")
+                        .takes_value(true)
+                        .help("path of Unix socket to listen"),
+                ),
         )
+        .subcommand(App::new("inspect").about("print configuration of the machine"))
         .get_matches();
 
+    let json = matches.get_one::<String>("format").expect("has default") == "json";
+    if let Err(failure) = run(&matches, json) {
+        match json {
+            true => println!(
+                "{}",
+                serde_json::json!({ "error": ErrorReport {
+                    kind: failure.kind,
+                    message: failure.error.to_string(),
+                }})
+            ),
+            false => eprintln!("error: {}", failure.error),
+        }
+        process::exit(failure.kind.exit_code());
+    }
+}
+
+fn run(matches: &ArgMatches, json: bool) -> Result<(), Failure> {
+    match matches.subcommand() {
+        Some(("keygen", matches)) => return keygen(matches),
+        Some(("serve", matches)) => return serve(matches),
+        _ => (),
+    }
+
+    let mut device = device(matches).map_err(Failure::configuration)?;
+    let notation: Notation = matches
+        .get_one::<String>("notation")
+        .expect("has default")
+        .parse()
+        .map_err(Failure::configuration)?;
+
+    match matches.subcommand() {
+        Some(("encrypt", sub)) | Some(("decrypt", sub)) => {
+            let groups = sub
+                .get_one::<String>("groups")
+                .unwrap()
+                .parse::<usize>()
+                .map_err(Failure::configuration)?;
+            let text = prepare(matches, &device, &read_input(sub)?);
+            print_crypt(&mut device, &text, groups, notation, json)?;
+        }
+        Some(("trace", sub)) => {
+            let text = match sub.get_one::<String>("text") {
+                Some(text) => text.clone(),
+                None => read_stdin()?,
+            };
+            for ch in prepare(matches, &device, &text).chars() {
+                if device.alphabet().contains(ch) {
+                    println!("{}", device.trace(ch)?);
+                }
+            }
+        }
+        Some(("analyze", sub)) => {
+            if sub.is_present("period") {
+                return print_period(&device);
+            }
+            let top = sub
+                .get_one::<String>("top")
+                .unwrap()
+                .parse::<usize>()
+                .map_err(Failure::configuration)?;
+            let text = prepare(matches, &device, &read_input(sub)?);
+            print_statistics(&device, &text);
+            if sub.is_present("attack") {
+                println!("best start positions:");
+                for (segments, ic) in stats::attack_positions(&device, &text, top)? {
+                    println!("  {} {:.4}", segments, ic);
+                }
+            }
+        }
+        Some(("batch", sub)) => {
+            let input = read_input(sub)?;
+            print_batch(device, &input, matches.is_present("transliterate"), json);
+        }
+        Some(("inspect", _)) => print_configuration(&device, notation),
+        _ => match json {
+            // json is one object, so all lines are read before encryption
+            true => {
+                let text = prepare(matches, &device, &read_stdin()?);
+                print_crypt(&mut device, &text, 0, notation, true)?;
+            }
+            false => crypt_lines(matches, &mut device),
+        },
+    }
+
+    return Ok(());
+}
+
+// settings of the machine, same for all commands
+fn machine_args() -> Vec<Arg<'static>> {
+    return vec![
+        Arg::with_name("plug-pairs")
+            .short('p')
+            .long("plug-pairs")
+            .global(true)
+            .help("plug pairs, like \"AV BS CG\", \"AV,BS,CG\" or \"AVBSCG\"")
+            .default_value(""),
+        Arg::with_name("uhr")
+            .long("uhr")
+            .global(true)
+            .takes_value(true)
+            .help("connect 10 plug pairs through Uhr box with the dial setting (00 - 39)"),
+        Arg::with_name("max-cables")
+            .long("max-cables")
+            .global(true)
+            .takes_value(true)
+            .help("max count of plug pairs, like 10 or 13 for historical key sheets"),
+        Arg::with_name("reflector")
+            .short('f')
+            .long("reflector")
+            .global(true)
+            .help("reflector (A-C, B-thin, C-thin, or from catalog), or its wiring")
+            .default_value("B"),
+        Arg::with_name("rotor")
+            .short('r')
+            .long("rotor")
+            .global(true)
+            .value_delimiter(',')
+            .help("rotors for usage (I - VIII, Beta, Gamma, or from catalog), or wirings with notches, like \"BDFHJLCPRTXVZNYEIWGAKMUSQO:V\". Suffix ' inserts rotor backwards, like \"II'\"")
+            .default_values(&["I", "II", "III"]),
+        Arg::with_name("segments")
+            .short('s')
+            .long("segments")
+            .global(true)
+            .value_delimiter(',')
+            .help("rotor settings, like: \"ABC\", or numbers of wheels, like \"01 02 03\"")
+            .default_value(""),
+        Arg::with_name("ring-offsets")
+            .short('o')
+            .long("ring-offsets")
+            .global(true)
+            .help("ring offsets, like \"ABC\", or numbers of wheels, like \"01 02 03\"")
+            .default_value(""),
+        Arg::with_name("pawls")
+            .long("pawls")
+            .global(true)
+            .takes_value(true)
+            .help("slots with pawl from left to right, like \"1111\". Rotors without pawl never turn. Every slot has pawl by default, except the left slot of M4 (thin rotor there, or four rotors with thin reflector)"),
+        Arg::with_name("notation")
+            .long("notation")
+            .global(true)
+            .possible_values(["letters", "numbers"])
+            .help("how rotor settings and ring offsets are shown, numbers like \"05 17 22\" are on wheels of Enigma I")
+            .default_value("letters"),
+        Arg::with_name("alphabet")
+            .long("alphabet")
+            .global(true)
+            .help("letters of the machine, rotors and reflector must be set by wirings for not A-Z alphabet")
+            .default_value(ALPHABET),
+        Arg::with_name("catalog")
+            .long("catalog")
+            .global(true)
+            .takes_value(true)
+            .help("TOML or JSON file with named rotors and reflectors"),
+        Arg::with_name("stepping")
+            .long("stepping")
+            .global(true)
+            .help("rotor stepping (ratchet, gear, odometer)")
+            .default_value("ratchet"),
+        Arg::with_name("format")
+            .long("format")
+            .global(true)
+            .possible_values(["text", "json"])
+            .help("output format. With json encryption result and errors are printed as JSON objects to stdout")
+            .default_value("text"),
+        Arg::with_name("transliterate")
+            .long("transliterate")
+            .global(true)
+            .takes_value(false)
+            .help("replace umlauts (Ä -> AE, ß -> SS) and fullwidth letters in settings and input"),
+    ];
+}
+
+fn device(matches: &ArgMatches) -> Result<Device, Box<dyn error::Error>> {
     let plug_pairs = matches
         .get_one::<String>("plug-pairs")
         .expect("can not be empty");
@@ -64,50 +412,349 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     let ring_offsets = matches
         .get_one::<String>("ring-offsets")
         .expect("can not be empty");
+    let alphabet = Alphabet::new(
+        matches
+            .get_one::<String>("alphabet")
+            .expect("can not be empty"),
+    )?;
+    let stepping = matches
+        .get_one::<String>("stepping")
+        .expect("can not be empty");
+    let stepping: Stepping = stepping
+        .parse()
+        .map_err(|_| BlockError::InvalidStepping(stepping.clone()))?;
+
+    let max_cables = match matches.get_one::<String>("max-cables") {
+        Some(max) => Some(max.parse::<usize>()?),
+        None => None,
+    };
+
+    let mut catalog = Catalog::with_alphabet(&alphabet);
+    if let Some(path) = matches.get_one::<String>("catalog") {
+        catalog.load(Path::new(path))?;
+    }
 
     // create device
-    let mut device = Device::new();
-    device.set_plug_pairs(plug_pairs.as_str())?;
+    let mut device = Device::with_alphabet(&alphabet);
+    let plug_pairs = prepare(matches, &device, plug_pairs);
+    match matches.get_one::<String>("uhr") {
+        Some(dial) => device.set_uhr(&plug_pairs, dial.parse::<usize>()?)?,
+        None => _ = device.set_cables(&plug_pairs, max_cables)?,
+    }
 
     if reflector.is_empty() == false {
-        device.set_reflector_type(reflector.as_str())?;
+        set_reflector(&mut device, &catalog, reflector)?;
     }
 
     for rotor in rotors.iter() {
-        device.add_rotor_type(rotor)?;
+        add_rotor(&mut device, &catalog, rotor)?;
+    }
+
+    device.set_stepping(stepping);
+
+    if let Some(pawls) = matches.get_one::<String>("pawls") {
+        device.set_pawls(&parse_pawls(pawls)?)?;
     }
 
     if segments.is_empty() == false {
-        device.set_segments(segments.as_str())?;
+        device.set_segments(&prepare(matches, &device, segments))?;
     }
 
     if ring_offsets.is_empty() == false {
-        device.set_ring_offsets(ring_offsets.as_str())?;
+        device.set_ring_offsets(&prepare(matches, &device, ring_offsets))?;
     }
 
-    let stdin = io::stdin();
-    loop {
-        let mut line = String::new();
-        match stdin.read_line(&mut line) {
-            Ok(n) => {
-                if n == 0 {
-                    break;
-                }
-                for ch in line.chars() {
-                    match device.crypt(ch) {
-                        Ok(out) => print!("{}", out),
-                        Err(_) => (), // just ignore all errors, like invalid symbol
-                    }
-                }
-            }
-            Err(error) => {
-                println!("error during reading input: {error}");
-                break;
+    return Ok(device);
+}
+
+// like "0111", 1 is a slot with pawl
+fn parse_pawls(pawls: &str) -> Result<Vec<bool>, String> {
+    return pawls
+        .chars()
+        .map(|ch| match ch {
+            '1' => Ok(true),
+            '0' => Ok(false),
+            _ => Err(format!("invalid pawl: {}, expected 0 or 1", ch)),
+        })
+        .collect();
+}
+
+// transliterate settings and input, if requested
+fn prepare<C: Cipher>(matches: &ArgMatches, cipher: &C, s: &str) -> String {
+    match matches.is_present("transliterate") {
+        true => cipher.alphabet().transliterate(s),
+        false => s.to_string(),
+    }
+}
+
+fn read_input(matches: &ArgMatches) -> Result<String, Failure> {
+    match matches.get_one::<String>("input").map(|s| s.as_str()) {
+        Some(path) if path != "-" => {
+            return fs::read_to_string(path)
+                .map_err(|error| Failure::input(format!("can not read {}: {}", path, error)));
+        }
+        _ => return read_stdin(),
+    }
+}
+
+fn read_stdin() -> Result<String, Failure> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(Failure::input)?;
+    return Ok(input);
+}
+
+// characters that are not in the alphabet are skipped, see cipher::crypt_text
+fn crypt<C: Cipher>(cipher: &mut C, text: &str, groups: usize) -> (String, Vec<Skipped>) {
+    let (output, skipped) = cipher::crypt_text(cipher, text);
+    let skipped = skipped
+        .into_iter()
+        .map(|(offset, character)| Skipped { offset, character })
+        .collect();
+
+    if groups == 0 {
+        return (output, skipped);
+    }
+    let letters: Vec<char> = output.chars().collect();
+    let output = letters
+        .chunks(groups)
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join(" ");
+    return (output, skipped);
+}
+
+fn print_crypt(
+    device: &mut Device,
+    text: &str,
+    groups: usize,
+    notation: Notation,
+    json: bool,
+) -> Result<(), Box<dyn error::Error>> {
+    let start = device.format_segments(notation);
+    let (output, skipped) = crypt(device, text, groups);
+    if json == false {
+        println!("{}", output);
+        return Ok(());
+    }
+
+    let report = CryptReport {
+        output,
+        start,
+        end: device.format_segments(notation),
+        ring_offsets: device.format_ring_offsets(notation),
+        plugboard: device.cables(),
+        uhr: device.uhr_dial(),
+        skipped,
+    };
+    println!("{}", serde_json::to_string(&report)?);
+    return Ok(());
+}
+
+// one result per record, errors of records don't stop the batch
+fn print_batch(device: Device, input: &str, transliterate: bool, json: bool) {
+    let mut batch = Batch::new(device);
+    batch.set_transliterate(transliterate);
+    for outcome in batch.run(input) {
+        match (outcome.result, json) {
+            (Ok(processed), true) => println!(
+                "{}",
+                serde_json::json!({
+                    "id": outcome.id,
+                    "key": processed.key,
+                    "output": processed.text,
+                    "skipped": processed.skipped,
+                })
+            ),
+            (Ok(processed), false) => {
+                println!("{}: {} {}", outcome.id, processed.key, processed.text)
             }
+            (Err(error), true) => println!(
+                "{}",
+                serde_json::json!({
+                    "id": outcome.id,
+                    "error": { "kind": "record", "message": error.to_string() },
+                })
+            ),
+            (Err(error), false) => println!("{}: error: {}", outcome.id, error),
         }
     }
+}
+
+// encrypt stdin line by line, without command
+fn crypt_lines<C: Cipher>(matches: &ArgMatches, cipher: &mut C) {
+    let transliterate = matches.is_present("transliterate");
+    let stdin = io::stdin();
+    let result = cipher::crypt_lines(cipher, stdin.lock(), &mut io::stdout(), transliterate);
+    if let Err(error) = result {
+        println!("error during reading input: {error}");
+    }
 
     println!();
+}
+
+// reflector from catalog, or wiring if it is as long as the alphabet
+fn set_reflector(
+    device: &mut Device,
+    catalog: &Catalog,
+    spec: &str,
+) -> Result<(), Box<dyn error::Error>> {
+    if spec.chars().count() == device.alphabet().len() {
+        return device.set_reflector_wiring(spec);
+    }
+    device.set_reflector(catalog.reflector(spec)?)?;
+    return Ok(());
+}
+
+// rotor from catalog, or wiring and notches separated by colon. Suffix `'` means
+// reversed rotor
+fn add_rotor(
+    device: &mut Device,
+    catalog: &Catalog,
+    spec: &str,
+) -> Result<(), Box<dyn error::Error>> {
+    let (wiring, notches) = match spec.split_once(':') {
+        Some(val) => val,
+        None => {
+            device.add_rotor(catalog.rotor(spec)?)?;
+            return Ok(());
+        }
+    };
+
+    let rotor = match notches.strip_suffix('\'') {
+        Some(notches) => Rotor::with_alphabet(device.alphabet(), wiring, notches)?.reversed(),
+        None => Rotor::with_alphabet(device.alphabet(), wiring, notches)?,
+    };
+    device.add_rotor(rotor)?;
+
+    return Ok(());
+}
+
+// address is printed before requests are accepted, so clients can wait for it
+fn serve(matches: &ArgMatches) -> Result<(), Failure> {
+    let server = Arc::new(Server::new());
+
+    if let Some(path) = matches.get_one::<String>("unix") {
+        return serve_unix(&server, path);
+    }
+
+    let address = match matches.get_one::<String>("tcp") {
+        Some(address) => address.as_str(),
+        None => "127.0.0.1:7941",
+    };
+    let listener = TcpListener::bind(address).map_err(Failure::configuration)?;
+    println!("listening on {}", listener.local_addr()?);
+    io::stdout().flush()?;
+    server.listen_tcp(listener)?;
+    return Ok(());
+}
+
+#[cfg(unix)]
+fn serve_unix(server: &Arc<Server>, path: &str) -> Result<(), Failure> {
+    let listener = UnixListener::bind(path).map_err(Failure::configuration)?;
+    println!("listening on {}", path);
+    io::stdout().flush()?;
+    server.listen_unix(listener)?;
+    return Ok(());
+}
+
+#[cfg(not(unix))]
+fn serve_unix(_: &Arc<Server>, _: &str) -> Result<(), Failure> {
+    return Err(Failure::configuration("Unix sockets are not supported"));
+}
+
+fn keygen(matches: &ArgMatches) -> Result<(), Failure> {
+    let machine: Machine = matches
+        .get_one::<String>("machine")
+        .unwrap()
+        .parse()
+        .map_err(Failure::configuration)?;
+    let days = matches
+        .get_one::<String>("days")
+        .unwrap()
+        .parse::<usize>()
+        .map_err(Failure::configuration)?;
+    let mut rng = match matches.get_one::<String>("seed") {
+        Some(seed) => StdRng::seed_from_u64(seed.parse::<u64>().map_err(Failure::configuration)?),
+        None => StdRng::from_entropy(),
+    };
+
+    let keys = Key::sheet(machine, days, &mut rng).map_err(Failure::configuration)?;
+    for key in keys {
+        println!("{}", key);
+    }
+
+    return Ok(());
+}
+
+fn print_configuration(device: &Device, notation: Notation) {
+    println!("alphabet: {}", device.alphabet());
+    match device.reflector() {
+        Some(reflector) => println!("reflector: {}", reflector.wiring()),
+        None => println!("reflector: none"),
+    }
+
+    for (slot, rotor) in device.rotors().iter().enumerate() {
+        println!(
+            "{} {}: {} notches: {} ring: {} position: {}{}",
+            if device.pawls()[slot] {
+                "rotor"
+            } else {
+                "stator"
+            },
+            slot + 1,
+            rotor.wiring(),
+            rotor.notches(),
+            notation.format(rotor, rotor.ring_offset()),
+            notation.format(rotor, rotor.segment()),
+            if rotor.is_reversed() { " reversed" } else { "" }
+        );
+    }
+
+    println!("stepping: {}", device.stepping());
+    match device.uhr_dial() {
+        Some(dial) => println!("uhr: {} dial: {:02}", device.cables(), dial),
+        None => println!("plugboard: {}", device.cables()),
+    }
+}
+
+fn print_statistics<C: Cipher>(cipher: &C, text: &str) {
+    let alphabet = cipher.alphabet();
+    let counts = stats::frequencies(alphabet, text);
+    let total: usize = counts.iter().map(|(_, count)| count).sum();
+
+    println!("letters: {}", total);
+    println!(
+        "index of coincidence: {:.4} (german text: {:.4}, random: {:.4})",
+        stats::index_of_coincidence(alphabet, text),
+        stats::IC_GERMAN,
+        1.0 / alphabet.len() as f64
+    );
+    println!("frequencies:");
+    for (letter, count) in counts.iter() {
+        let percent = match total {
+            0 => 0.0,
+            _ => *count as f64 * 100.0 / total as f64,
+        };
+        println!("  {} {:>6} {:>5.1}%", letter, count, percent);
+    }
+}
+
+fn print_period(device: &Device) -> Result<(), Failure> {
+    let analysis = device.period_analysis()?;
+
+    println!("stepping: {}", device.stepping());
+    println!("period: {}", analysis.period());
+    println!("cycles: {}", analysis.cycles.len());
+    for cycle in analysis.cycles.iter() {
+        println!("  {} positions starting at {}", cycle.length, cycle.start);
+    }
+    println!("reachable positions: {}", analysis.reachable.len());
+    println!("never reached again: {}", analysis.unreachable.len());
+    for chunk in analysis.unreachable.chunks(16) {
+        println!("  {}", chunk.join(" "));
+    }
 
     return Ok(());
 }
@@ -0,0 +1,464 @@
+// Python extension module `enigma`, built with `python` feature, see
+// pyproject.toml. Objects passed to other objects are copied, like rotors
+// passed to `Device.from_parts`, so changes of the original don't affect the
+// device.
+use crate::device::alphabet::AlphabetError;
+use crate::device::block::BlockError;
+use crate::device::builder::BuildError;
+use crate::device::builder::DeviceBuilder;
+use crate::device::details::SegmentError;
+use crate::device::plugboard::PlugBoard;
+use crate::device::plugboard::PlugBoardError;
+use crate::device::reflector::Reflector;
+use crate::device::reflector::ReflectorError;
+use crate::device::rotor::Rotor;
+use crate::device::rotor::RotorError;
+use crate::device::trace::Trace;
+use crate::device::Device;
+use crate::device::DeviceError;
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::error;
+
+create_exception!(enigma, EnigmaError, PyValueError);
+create_exception!(enigma, SettingsError, EnigmaError);
+create_exception!(enigma, PlugboardError, EnigmaError);
+create_exception!(enigma, InvalidCharacterError, EnigmaError);
+
+fn to_py(err: Box<dyn error::Error>) -> PyErr {
+    let message = err.to_string();
+    if let Some(DeviceError::InvalidCharacter(_)) = err.downcast_ref::<DeviceError>() {
+        return InvalidCharacterError::new_err(message);
+    }
+    if err.is::<PlugBoardError>() {
+        return PlugboardError::new_err(message);
+    }
+    if err.is::<BuildError>()
+        || err.is::<RotorError>()
+        || err.is::<ReflectorError>()
+        || err.is::<BlockError>()
+        || err.is::<SegmentError>()
+        || err.is::<AlphabetError>()
+        || err.is::<DeviceError>()
+    {
+        return SettingsError::new_err(message);
+    }
+    return EnigmaError::new_err(message);
+}
+
+#[pyclass(name = "Rotor", module = "enigma")]
+#[derive(Clone)]
+pub struct PyRotor {
+    rotor: Rotor,
+}
+
+#[pymethods]
+impl PyRotor {
+    #[new]
+    #[pyo3(signature = (wiring, notches = "", ring_offset = 'A', position = 'A'))]
+    fn new(wiring: &str, notches: &str, ring_offset: char, position: char) -> PyResult<Self> {
+        let mut rotor = Rotor::new(wiring, notches).map_err(to_py)?;
+        rotor
+            .set_ring_offset(ring_offset)
+            .map_err(|err| to_py(err.into()))?;
+        rotor
+            .set_segment(position)
+            .map_err(|err| to_py(err.into()))?;
+        return Ok(Self { rotor });
+    }
+
+    // historical rotor, like "I" or "Beta"
+    #[staticmethod]
+    fn model(name: &str) -> PyResult<Self> {
+        let rotor = Rotor::model(name).map_err(to_py)?;
+        return Ok(Self { rotor });
+    }
+
+    #[getter]
+    fn wiring(&self) -> &str {
+        return self.rotor.wiring();
+    }
+
+    #[getter]
+    fn notches(&self) -> &str {
+        return self.rotor.notches();
+    }
+
+    #[getter]
+    fn position(&self) -> char {
+        return self.rotor.segment();
+    }
+
+    #[setter]
+    fn set_position(&mut self, position: char) -> PyResult<()> {
+        self.rotor
+            .set_segment(position)
+            .map_err(|err| to_py(err.into()))?;
+        return Ok(());
+    }
+
+    #[getter]
+    fn ring_offset(&self) -> char {
+        return self.rotor.ring_offset();
+    }
+
+    #[setter]
+    fn set_ring_offset(&mut self, offset: char) -> PyResult<()> {
+        self.rotor
+            .set_ring_offset(offset)
+            .map_err(|err| to_py(err.into()))?;
+        return Ok(());
+    }
+
+    #[getter]
+    fn is_reversed(&self) -> bool {
+        return self.rotor.is_reversed();
+    }
+
+    fn reversed(&self) -> Self {
+        return Self {
+            rotor: self.rotor.reversed(),
+        };
+    }
+
+    // true if rotor on the left must step too
+    fn advance(&mut self) -> bool {
+        return self.rotor.advance();
+    }
+
+    fn forward(&self, ch: char) -> PyResult<char> {
+        return self.rotor.forward(ch).map_err(|err| to_py(err.into()));
+    }
+
+    fn backward(&self, ch: char) -> PyResult<char> {
+        return self.rotor.backward(ch).map_err(|err| to_py(err.into()));
+    }
+
+    fn __repr__(&self) -> String {
+        return format!(
+            "Rotor('{}', notches='{}', ring_offset='{}', position='{}')",
+            self.rotor.wiring(),
+            self.rotor.notches(),
+            self.rotor.ring_offset(),
+            self.rotor.segment()
+        );
+    }
+}
+
+#[pyclass(name = "Reflector", module = "enigma")]
+#[derive(Clone)]
+pub struct PyReflector {
+    reflector: Reflector,
+}
+
+#[pymethods]
+impl PyReflector {
+    #[new]
+    fn new(wiring: &str) -> PyResult<Self> {
+        let reflector = Reflector::new(wiring).map_err(to_py)?;
+        return Ok(Self { reflector });
+    }
+
+    // historical reflector, like "B" or "C-thin"
+    #[staticmethod]
+    fn model(name: &str) -> PyResult<Self> {
+        let reflector = Reflector::model(name).map_err(to_py)?;
+        return Ok(Self { reflector });
+    }
+
+    #[getter]
+    fn wiring(&self) -> &str {
+        return self.reflector.wiring();
+    }
+
+    fn reflect(&self, ch: char) -> PyResult<char> {
+        return self.reflector.forward(ch).map_err(|err| to_py(err.into()));
+    }
+
+    fn __repr__(&self) -> String {
+        return format!("Reflector('{}')", self.reflector.wiring());
+    }
+}
+
+#[pyclass(name = "PlugBoard", module = "enigma")]
+#[derive(Clone)]
+pub struct PyPlugBoard {
+    board: PlugBoard,
+}
+
+#[pymethods]
+impl PyPlugBoard {
+    #[new]
+    #[pyo3(signature = (pairs = ""))]
+    fn new(pairs: &str) -> PyResult<Self> {
+        let mut board = PlugBoard::new();
+        board
+            .set_cables(pairs, None)
+            .map_err(|err| to_py(err.into()))?;
+        return Ok(Self { board });
+    }
+
+    #[getter]
+    fn pairs(&self) -> String {
+        return self.board.cables();
+    }
+
+    fn connect(&mut self, first: char, second: char) -> PyResult<()> {
+        self.board
+            .add_pair(first, second)
+            .map_err(|err| to_py(err.into()))?;
+        return Ok(());
+    }
+
+    // returns removed pair
+    fn disconnect(&mut self, ch: char) -> PyResult<(char, char)> {
+        return self.board.remove_pair(ch).map_err(|err| to_py(err.into()));
+    }
+
+    fn clear(&mut self) {
+        self.board.clear();
+    }
+
+    fn swap(&self, ch: char) -> PyResult<char> {
+        return self.board.crypt(ch).map_err(|err| to_py(err.into()));
+    }
+
+    fn __repr__(&self) -> String {
+        return format!("PlugBoard('{}')", self.board.cables());
+    }
+}
+
+// path of one key press, `steps` are tuples of stage, input and output
+#[pyclass(name = "Trace", module = "enigma", get_all)]
+pub struct PyTrace {
+    input: char,
+    output: char,
+    positions: String,
+    steps: Vec<(String, char, char)>,
+    text: String,
+}
+
+impl From<Trace> for PyTrace {
+    fn from(trace: Trace) -> Self {
+        return Self {
+            input: trace.input,
+            output: trace.output,
+            positions: trace.positions.clone(),
+            steps: trace
+                .steps
+                .iter()
+                .map(|step| (step.stage.to_string(), step.input, step.output))
+                .collect(),
+            text: trace.to_string(),
+        };
+    }
+}
+
+#[pymethods]
+impl PyTrace {
+    fn __str__(&self) -> &str {
+        return &self.text;
+    }
+
+    fn __repr__(&self) -> String {
+        return format!(
+            "Trace('{}' -> '{}' at '{}')",
+            self.input, self.output, self.positions
+        );
+    }
+}
+
+// Encryption of the letters one by one, so the device moves only for taken
+// traces. Characters not in the alphabet are skipped
+#[pyclass(name = "TraceIterator", module = "enigma")]
+pub struct PyTraceIterator {
+    device: Py<PyDevice>,
+    letters: Vec<char>,
+    next: usize,
+}
+
+#[pymethods]
+impl PyTraceIterator {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        return slf;
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<PyTrace>> {
+        let mut device = self.device.borrow_mut(py);
+        while let Some(ch) = self.letters.get(self.next).copied() {
+            self.next += 1;
+            if device.device.alphabet().contains(ch) {
+                let trace = device.device.trace(ch).map_err(to_py)?;
+                return Ok(Some(trace.into()));
+            }
+        }
+        return Ok(None);
+    }
+}
+
+#[pyclass(name = "Device", module = "enigma")]
+pub struct PyDevice {
+    device: Device,
+}
+
+#[pymethods]
+impl PyDevice {
+    // settings are checked for the machine, like by key sheets:
+    // Device(rotors=["II", "IV", "V"], ring_offsets="BUL", positions="BLA")
+    #[new]
+    #[pyo3(signature = (
+        machine = "enigma-i",
+        reflector = "B",
+        rotors = vec!["I".to_string(), "II".to_string(), "III".to_string()],
+        ring_offsets = "",
+        positions = "",
+        plug_pairs = ""
+    ))]
+    fn new(
+        machine: &str,
+        reflector: &str,
+        rotors: Vec<String>,
+        ring_offsets: &str,
+        positions: &str,
+        plug_pairs: &str,
+    ) -> PyResult<Self> {
+        let machine = machine
+            .parse()
+            .map_err(|err: BuildError| to_py(err.into()))?;
+        let device = DeviceBuilder::new(machine)
+            .reflector(reflector)
+            .walzenlage(&rotors)
+            .ringstellung(ring_offsets)
+            .grundstellung(positions)
+            .steckers(plug_pairs)
+            .build()
+            .map_err(|err| to_py(err.into()))?;
+        return Ok(Self { device });
+    }
+
+    // device of any parts, without checks of historical machines
+    #[staticmethod]
+    #[pyo3(signature = (reflector, rotors, plugboard = None))]
+    fn from_parts(
+        reflector: PyRef<PyReflector>,
+        rotors: Vec<PyRef<PyRotor>>,
+        plugboard: Option<PyRef<PyPlugBoard>>,
+    ) -> PyResult<Self> {
+        let mut device = Device::new();
+        device
+            .set_reflector(reflector.reflector.clone())
+            .map_err(|err| to_py(err.into()))?;
+        for rotor in rotors.iter() {
+            device
+                .add_rotor(rotor.rotor.clone())
+                .map_err(|err| to_py(err.into()))?;
+        }
+        if let Some(plugboard) = plugboard {
+            device
+                .set_plugboard(plugboard.board.clone())
+                .map_err(|err| to_py(err.into()))?;
+        }
+        return Ok(Self { device });
+    }
+
+    // characters not in the alphabet are skipped, like by command line tool
+    fn encrypt(&mut self, text: &str) -> String {
+        return text
+            .chars()
+            .filter_map(|ch| self.device.crypt(ch).ok())
+            .collect();
+    }
+
+    // same as encrypt, Enigma is reciprocal
+    fn decrypt(&mut self, text: &str) -> String {
+        return self.encrypt(text);
+    }
+
+    fn trace(slf: Py<Self>, text: &str) -> PyTraceIterator {
+        return PyTraceIterator {
+            device: slf,
+            letters: text.chars().collect(),
+            next: 0,
+        };
+    }
+
+    #[getter]
+    fn positions(&self) -> String {
+        return self.device.segments();
+    }
+
+    #[setter]
+    fn set_positions(&mut self, positions: &str) -> PyResult<()> {
+        self.device.set_segments(positions).map_err(to_py)?;
+        return Ok(());
+    }
+
+    #[getter]
+    fn ring_offsets(&self) -> String {
+        return self.device.ring_offsets();
+    }
+
+    #[setter]
+    fn set_ring_offsets(&mut self, offsets: &str) -> PyResult<()> {
+        self.device.set_ring_offsets(offsets).map_err(to_py)?;
+        return Ok(());
+    }
+
+    #[getter]
+    fn plug_pairs(&self) -> String {
+        return self.device.cables();
+    }
+
+    #[setter]
+    fn set_plug_pairs(&mut self, pairs: &str) -> PyResult<()> {
+        self.device.set_cables(pairs, None).map_err(to_py)?;
+        return Ok(());
+    }
+
+    #[getter]
+    fn rotors(&self) -> Vec<PyRotor> {
+        return self
+            .device
+            .rotors()
+            .iter()
+            .map(|rotor| PyRotor {
+                rotor: rotor.clone(),
+            })
+            .collect();
+    }
+
+    #[getter]
+    fn reflector(&self) -> Option<PyReflector> {
+        return self.device.reflector().map(|reflector| PyReflector {
+            reflector: reflector.clone(),
+        });
+    }
+
+    fn __repr__(&self) -> String {
+        return format!(
+            "Device(positions='{}', ring_offsets='{}', plug_pairs='{}')",
+            self.device.segments(),
+            self.device.ring_offsets(),
+            self.device.cables()
+        );
+    }
+}
+
+#[pymodule]
+fn enigma(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyDevice>()?;
+    m.add_class::<PyRotor>()?;
+    m.add_class::<PyReflector>()?;
+    m.add_class::<PyPlugBoard>()?;
+    m.add_class::<PyTrace>()?;
+    m.add_class::<PyTraceIterator>()?;
+    m.add("EnigmaError", m.py().get_type::<EnigmaError>())?;
+    m.add("SettingsError", m.py().get_type::<SettingsError>())?;
+    m.add("PlugboardError", m.py().get_type::<PlugboardError>())?;
+    m.add(
+        "InvalidCharacterError",
+        m.py().get_type::<InvalidCharacterError>(),
+    )?;
+    return Ok(());
+}
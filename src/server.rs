@@ -0,0 +1,312 @@
+use crate::cipher::crypt_text;
+use crate::device::builder::DeviceBuilder;
+use crate::device::builder::Machine;
+use crate::device::Device;
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+
+// Line-delimited JSON protocol: every request is one JSON object on a line,
+// every response is one line too. Responses have `"ok": true` and results of
+// the request, or `"ok": false` and `"error"` with the reason.
+//
+// {"op": "create", "settings": {"rotors": ["II", "IV", "V"], "positions": "BLA"}}
+// {"op": "encrypt", "session": 1, "text": "EDPUD"}
+// {"op": "set_positions", "session": 1, "positions": "BLA"}
+// {"op": "state", "session": 1}
+// {"op": "close", "session": 1}
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Request {
+    Create {
+        #[serde(default)]
+        settings: Settings,
+    },
+    Encrypt {
+        session: u64,
+        text: String,
+    },
+    SetPositions {
+        session: u64,
+        positions: String,
+    },
+    State {
+        session: u64,
+    },
+    Close {
+        session: u64,
+    },
+}
+
+// settings of a session, not set fields have values of Enigma I with rotors
+// I II III and reflector B
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub machine: String,
+    pub reflector: String,
+    pub rotors: Vec<String>,
+    pub ring_offsets: String,
+    pub positions: String,
+    pub plug_pairs: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        return Self {
+            machine: Machine::EnigmaI.to_string(),
+            reflector: "B".to_string(),
+            rotors: vec!["I".to_string(), "II".to_string(), "III".to_string()],
+            ring_offsets: String::new(),
+            positions: String::new(),
+            plug_pairs: String::new(),
+        };
+    }
+}
+
+impl Settings {
+    pub fn device(&self) -> Result<Device, Box<dyn error::Error>> {
+        let device = DeviceBuilder::new(self.machine.parse()?)
+            .reflector(&self.reflector)
+            .walzenlage(&self.rotors)
+            .ringstellung(&self.ring_offsets)
+            .grundstellung(&self.positions)
+            .steckers(&self.plug_pairs)
+            .build()?;
+        return Ok(device);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ServerError {
+    InvalidRequest(String),
+    UnknownSession(u64),
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServerError::InvalidRequest(reason) => write!(f, "invalid request: {}", reason),
+            ServerError::UnknownSession(id) => write!(f, "unknown session: {}", id),
+        }
+    }
+}
+
+impl error::Error for ServerError {}
+
+#[derive(Default)]
+struct Sessions {
+    last: u64,
+    devices: HashMap<u64, Device>,
+}
+
+// Sessions are kept in memory and shared by all connections, so a session
+// created by one client can be used by another one. Sessions are lost on exit
+#[derive(Default)]
+pub struct Server {
+    sessions: Mutex<Sessions>,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    pub fn sessions(&self) -> usize {
+        return self.sessions.lock().expect("not poisoned").devices.len();
+    }
+
+    // response line for the request line
+    pub fn handle(&self, line: &str) -> String {
+        let result = serde_json::from_str::<Request>(line)
+            .map_err(|err| ServerError::InvalidRequest(err.to_string()).into())
+            .and_then(|request| self.execute(request));
+
+        let response = match result {
+            Ok(Value::Object(mut fields)) => {
+                fields.insert("ok".to_string(), Value::Bool(true));
+                Value::Object(fields)
+            }
+            Ok(_) => json!({ "ok": true }),
+            Err(err) => json!({ "ok": false, "error": err.to_string() }),
+        };
+        return response.to_string();
+    }
+
+    pub fn execute(&self, request: Request) -> Result<Value, Box<dyn error::Error>> {
+        let mut sessions = self.sessions.lock().expect("not poisoned");
+
+        let session = match request {
+            Request::Create { settings } => {
+                let device = settings.device()?;
+                sessions.last += 1;
+                let id = sessions.last;
+                sessions.devices.insert(id, device);
+                return Ok(json!({ "session": id }));
+            }
+            Request::Close { session } => {
+                return match sessions.devices.remove(&session) {
+                    Some(_) => Ok(json!({})),
+                    None => Err(ServerError::UnknownSession(session).into()),
+                };
+            }
+            Request::Encrypt { session, .. }
+            | Request::SetPositions { session, .. }
+            | Request::State { session } => session,
+        };
+
+        let device = sessions
+            .devices
+            .get_mut(&session)
+            .ok_or(ServerError::UnknownSession(session))?;
+
+        match request {
+            Request::Encrypt { text, .. } => {
+                // characters not in the alphabet are skipped, like by CLI
+                let (output, skipped) = crypt_text(device, &text);
+                return Ok(json!({
+                    "output": output,
+                    "skipped": skipped.len(),
+                    "positions": device.segments(),
+                }));
+            }
+            Request::SetPositions { positions, .. } => {
+                device.set_segments(&positions)?;
+                return Ok(json!({ "positions": device.segments() }));
+            }
+            _ => {
+                return Ok(json!({
+                    "positions": device.segments(),
+                    "ring_offsets": device.ring_offsets(),
+                    "plug_pairs": device.cables(),
+                }));
+            }
+        }
+    }
+
+    // requests of one connection until it is closed by client
+    pub fn serve<S: Read + Write>(&self, stream: S) -> io::Result<()> {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut response = self.handle(&line);
+            response.push('\n');
+            let stream = reader.get_mut();
+            stream.write_all(response.as_bytes())?;
+            stream.flush()?;
+        }
+    }
+
+    // every connection is served by own thread
+    pub fn listen_tcp(self: &Arc<Self>, listener: TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let server = Arc::clone(self);
+            thread::spawn(move || server.serve(stream));
+        }
+        return Ok(());
+    }
+
+    #[cfg(unix)]
+    pub fn listen_unix(self: &Arc<Self>, listener: UnixListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let server = Arc::clone(self);
+            thread::spawn(move || server.serve(stream));
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::Server;
+    use serde_json::Value;
+
+    fn request(server: &Server, line: &str) -> Value {
+        return serde_json::from_str(&server.handle(line)).unwrap();
+    }
+
+    #[test]
+    fn session() {
+        let server = Server::new();
+
+        let response = request(
+            &server,
+            r#"{"op": "create", "settings": {"rotors": ["II", "IV", "V"], "ring_offsets": "BUL",
+                "positions": "BLA", "plug_pairs": "AV BS CG DL FU HZ IN KM OW RX"}}"#,
+        );
+        assert_eq!(response["ok"], true);
+        let session = response["session"].as_u64().unwrap();
+
+        let line = format!(
+            r#"{{"op": "encrypt", "session": {}, "text": "EDPUD !"}}"#,
+            session
+        );
+        let response = request(&server, &line);
+        assert_eq!(response["output"], "AUFKL");
+        assert_eq!(response["skipped"], 1);
+        assert_eq!(response["positions"], "BLF");
+
+        let line = format!(
+            r#"{{"op": "set_positions", "session": {}, "positions": "BLA"}}"#,
+            session
+        );
+        assert_eq!(request(&server, &line)["positions"], "BLA");
+
+        let line = format!(r#"{{"op": "state", "session": {}}}"#, session);
+        let response = request(&server, &line);
+        assert_eq!(response["ring_offsets"], "BUL");
+        assert_eq!(response["plug_pairs"], "AV BS CG DL FU HZ IN KM OW RX");
+
+        let line = format!(r#"{{"op": "close", "session": {}}}"#, session);
+        assert_eq!(request(&server, &line)["ok"], true);
+        assert_eq!(request(&server, &line)["ok"], false);
+        assert_eq!(server.sessions(), 0);
+    }
+
+    #[test]
+    fn errors() {
+        let server = Server::new();
+        for line in [
+            "not json",
+            r#"{"op": "unknown"}"#,
+            r#"{"op": "state", "session": 1}"#,
+            r#"{"op": "create", "settings": {"rotors": ["I", "II"]}}"#,
+            r#"{"op": "create", "settings": {"machine": "m5"}}"#,
+        ] {
+            let response = request(&server, line);
+            assert_eq!(response["ok"], false, "{}", line);
+            assert!(response["error"].is_string());
+        }
+
+        let session = request(&server, r#"{"op": "create"}"#)["session"].clone();
+        let line = format!(
+            r#"{{"op": "set_positions", "session": {}, "positions": "A1"}}"#,
+            session
+        );
+        assert_eq!(request(&server, &line)["ok"], false);
+    }
+}
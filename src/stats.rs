@@ -0,0 +1,166 @@
+use crate::cipher::Cipher;
+use crate::device::alphabet::Alphabet;
+use crate::device::period::MAX_STATES;
+use std::error;
+use std::fmt;
+
+// index of coincidence of German plain text, random text has 1/26 (0.038)
+pub const IC_GERMAN: f64 = 0.0762;
+
+#[derive(Debug, Clone)]
+pub enum StatsError {
+    TooManyStates(/*actual*/ usize, /*max*/ usize),
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatsError::TooManyStates(actual, max) => write!(
+                f,
+                "too many rotor positions for attack: {}/{} (actual/max)",
+                actual, max
+            ),
+        }
+    }
+}
+
+impl error::Error for StatsError {}
+
+// count of every letter of the alphabet in the text, other characters are
+// skipped
+pub fn frequencies(alphabet: &Alphabet, text: &str) -> Vec<(char, usize)> {
+    let mut counts = vec![0; alphabet.len()];
+    for ch in text.chars() {
+        if let Some(index) = alphabet.index(ch) {
+            counts[index] += 1;
+        }
+    }
+
+    return alphabet.letters().iter().copied().zip(counts).collect();
+}
+
+// probability that two letters taken from the text are same. Enigma output
+// is close to random, so plain text can be found by higher index
+pub fn index_of_coincidence(alphabet: &Alphabet, text: &str) -> f64 {
+    let counts = frequencies(alphabet, text);
+    let total: usize = counts.iter().map(|(_, count)| count).sum();
+    if total < 2 {
+        return 0.0;
+    }
+
+    let pairs: usize = counts
+        .iter()
+        .map(|(_, count)| count * count.saturating_sub(1))
+        .sum();
+    return pairs as f64 / (total * (total - 1)) as f64;
+}
+
+// Ciphertext-only attack on start positions: every position of the moving
+// wheels is tried with other settings of the machine, and positions which give
+// text with the highest index of coincidence are returned first. Works well
+// when the machine has all settings except of positions, and no or few cables.
+pub fn attack_positions<C: Cipher + Clone>(
+    cipher: &C,
+    ciphertext: &str,
+    top: usize,
+) -> Result<Vec<(String, f64)>, Box<dyn error::Error>> {
+    let alphabet = cipher.alphabet();
+    let slots: Vec<usize> = cipher
+        .moving()
+        .iter()
+        .enumerate()
+        .filter(|(_, moving)| **moving)
+        .map(|(slot, _)| slot)
+        .collect();
+
+    let states = u32::try_from(slots.len())
+        .ok()
+        .and_then(|count| alphabet.len().checked_pow(count))
+        .unwrap_or(usize::MAX);
+    if states > MAX_STATES {
+        return Err(StatsError::TooManyStates(states, MAX_STATES).into());
+    }
+
+    let letters: Vec<char> = ciphertext
+        .chars()
+        .filter(|ch| alphabet.contains(*ch))
+        .collect();
+
+    let mut cipher = cipher.clone();
+    let mut results = Vec::with_capacity(states);
+    // stators keep their positions
+    let mut all: Vec<char> = cipher.positions().chars().collect();
+    for state in 0..states {
+        // left wheel is the most significant
+        let mut rest = state;
+        for slot in slots.iter().rev() {
+            all[*slot] = cipher.alphabet().letter(rest % cipher.alphabet().len());
+            rest /= cipher.alphabet().len();
+        }
+        let positions: String = all.iter().collect();
+
+        cipher.set_positions(&positions)?;
+        let mut plaintext = String::with_capacity(letters.len());
+        for ch in letters.iter() {
+            plaintext.push(cipher.crypt(*ch)?);
+        }
+
+        let ic = index_of_coincidence(cipher.alphabet(), &plaintext);
+        results.push((positions, ic));
+    }
+
+    results.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    results.truncate(top);
+    return Ok(results);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device::alphabet::Alphabet;
+    use crate::device::Device;
+    use crate::stats::attack_positions;
+    use crate::stats::frequencies;
+    use crate::stats::index_of_coincidence;
+
+    const PLAINTEXT: &str = "FEINDLIQEINFANTERIEKOLONNEBEOBAQTETXANFANGSUEDAUSGANGBAERWALDE\
+                             XENDEDREIKMOSTWAERTSNEUSTADT";
+
+    #[test]
+    fn statistics() {
+        let alphabet = Alphabet::latin();
+        let counts = frequencies(&alphabet, "Hello, world!");
+        assert_eq!(counts.len(), 26);
+        assert_eq!(counts[11], ('L', 3));
+        assert_eq!(counts[0], ('A', 0));
+
+        assert_eq!(index_of_coincidence(&alphabet, "AAAA"), 1.0);
+        assert_eq!(index_of_coincidence(&alphabet, "ABCD"), 0.0);
+        assert_eq!(index_of_coincidence(&alphabet, "A"), 0.0);
+        assert!(index_of_coincidence(&alphabet, PLAINTEXT) > 0.06);
+    }
+
+    #[test]
+    fn attack() {
+        let mut device = Device::new();
+        device.set_reflector_type("B").unwrap();
+        device.add_rotor_type("II").unwrap();
+        device.add_rotor_type("V").unwrap();
+        device.set_segments("QG").unwrap();
+
+        let ciphertext: String = PLAINTEXT
+            .chars()
+            .map(|ch| device.crypt(ch).unwrap())
+            .collect();
+        assert!(index_of_coincidence(device.alphabet(), &ciphertext) < 0.06);
+
+        let results = attack_positions(&device, &ciphertext, 3).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].0, "QG");
+        assert!(results[0].1 > results[1].1);
+
+        device.add_rotor_type("I").unwrap();
+        device.add_rotor_type("III").unwrap();
+        device.add_rotor_type("IV").unwrap();
+        assert!(attack_positions(&device, &ciphertext, 3).is_err());
+    }
+}
@@ -0,0 +1,330 @@
+use crate::device::block::Block;
+use crate::device::details::ALPHABET;
+use crate::device::reflector::Reflector;
+use crate::device::rotor::Rotor;
+use crate::device::rotor::RotorError;
+use std::error;
+use std::fmt;
+
+// Sample wirings for Typex rotors. Real Typex wirings were changed regularly
+// and are mostly not published, so these are NOT historical wirings, but
+// they have same structure: five notches on each rotor.
+pub const TYPEX_RING_A: &str = "FKTYWCQXGZAOIVJHPLBDMRNUES";
+pub const TYPEX_RING_B: &str = "XSGFZYWIUVQKBPTDLNOHAJCREM";
+pub const TYPEX_RING_C: &str = "GJZTSIFDEWRBYLUMAXVPKHOQNC";
+pub const TYPEX_RING_D: &str = "ISBGFQJATNVMCEODYXLKUZHRWP";
+pub const TYPEX_RING_E: &str = "NVCQMEBAGOIZLFWXRPHUDYSTJK";
+pub const TYPEX_RING_F: &str = "JOMKEPQIFADGCZBRVSLYTXNUWH";
+pub const TYPEX_RING_G: &str = "BYQOSDJNLWPFAGIKEHTURMVXCZ";
+pub const TYPEX_REFLECTOR: &str = "LUNFODHGTKJAVCESYZPIBMXWQR";
+
+pub const TYPEX_NOTCH_A: &str = "CLOUY";
+pub const TYPEX_NOTCH_B: &str = "AIKMO";
+pub const TYPEX_NOTCH_C: &str = "DINRX";
+pub const TYPEX_NOTCH_D: &str = "BEJOY";
+pub const TYPEX_NOTCH_E: &str = "GIKNS";
+pub const TYPEX_NOTCH_F: &str = "FJRYZ";
+pub const TYPEX_NOTCH_G: &str = "DLOUY";
+
+pub const TYPEX_ROTORS: usize = 3;
+pub const TYPEX_STATORS: usize = 2;
+
+// keyboard codes of the printer
+const SPACE: char = 'X';
+const FIGURES_SHIFT: char = 'Z';
+const LETTERS_SHIFT: char = 'V';
+
+// figures shift of the top row of the keyboard
+const FIGURES: [(char, char); 10] = [
+    ('Q', '1'),
+    ('W', '2'),
+    ('E', '3'),
+    ('R', '4'),
+    ('T', '5'),
+    ('Y', '6'),
+    ('U', '7'),
+    ('I', '8'),
+    ('O', '9'),
+    ('P', '0'),
+];
+
+// British Typex: reflector, three moving rotors and two stators on the right
+// (entry) side. Stators can be set to any position, but never turn. Any rotor
+// can be inserted backwards.
+// Printer of Typex used letters X as space, Z as figures shift and V as
+// letters shift, so plain text can contain digits and spaces, but these
+// three letters are interpreted as control codes after decryption.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Typex {
+    block: Block,
+}
+
+#[derive(Debug, Clone)]
+pub enum TypexError {
+    TooManyRotors(/*max*/ usize),
+    TooManyStators(/*max*/ usize),
+    NotConfigured(/*rotors*/ usize, /*stators*/ usize),
+}
+
+impl fmt::Display for TypexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TypexError::TooManyRotors(max) => write!(f, "too many moving rotors, max: {}", max),
+            TypexError::TooManyStators(max) => write!(f, "too many stators, max: {}", max),
+            TypexError::NotConfigured(rotors, stators) => write!(
+                f,
+                "typex requires {} moving rotors and {} stators, but have {} and {}",
+                TYPEX_ROTORS, TYPEX_STATORS, rotors, stators
+            ),
+        }
+    }
+}
+
+impl error::Error for TypexError {}
+
+impl Typex {
+    pub fn new() -> Self {
+        let mut block = Block::new();
+        block.set_reflector(Reflector::new(TYPEX_REFLECTOR).expect("valid sample reflector"));
+
+        Self { block }
+    }
+
+    pub fn set_reflector(&mut self, reflector: Reflector) {
+        self.block.set_reflector(reflector);
+    }
+
+    pub fn rotor(rotor_type: &str, reversed: bool) -> Result<Rotor, Box<dyn error::Error>> {
+        let val = rotor_type.to_uppercase();
+
+        let (wiring, notches) = if val == "A" {
+            (TYPEX_RING_A, TYPEX_NOTCH_A)
+        } else if val == "B" {
+            (TYPEX_RING_B, TYPEX_NOTCH_B)
+        } else if val == "C" {
+            (TYPEX_RING_C, TYPEX_NOTCH_C)
+        } else if val == "D" {
+            (TYPEX_RING_D, TYPEX_NOTCH_D)
+        } else if val == "E" {
+            (TYPEX_RING_E, TYPEX_NOTCH_E)
+        } else if val == "F" {
+            (TYPEX_RING_F, TYPEX_NOTCH_F)
+        } else if val == "G" {
+            (TYPEX_RING_G, TYPEX_NOTCH_G)
+        } else {
+            return Err(RotorError::InvalidRotorType(val).into());
+        };
+
+        let rotor = Rotor::new(wiring, notches)?;
+        if reversed {
+            return Ok(rotor.reversed());
+        }
+        return Ok(rotor);
+    }
+
+    // from left to right
+    pub fn add_rotor(&mut self, rotor: Rotor) -> Result<(), TypexError> {
+        if self.block.rotors().len() - self.block.stators() == TYPEX_ROTORS {
+            return Err(TypexError::TooManyRotors(TYPEX_ROTORS));
+        }
+        self.block.add_rotor(rotor);
+        Ok(())
+    }
+
+    // from left to right, after moving rotors
+    pub fn add_stator(&mut self, stator: Rotor) -> Result<(), TypexError> {
+        if self.block.stators() == TYPEX_STATORS {
+            return Err(TypexError::TooManyStators(TYPEX_STATORS));
+        }
+        self.block.add_stator(stator);
+        Ok(())
+    }
+
+    pub fn add_rotor_type(
+        &mut self,
+        rotor_type: &str,
+        reversed: bool,
+    ) -> Result<(), Box<dyn error::Error>> {
+        self.add_rotor(Self::rotor(rotor_type, reversed)?)?;
+        Ok(())
+    }
+
+    pub fn add_stator_type(
+        &mut self,
+        rotor_type: &str,
+        reversed: bool,
+    ) -> Result<(), Box<dyn error::Error>> {
+        self.add_stator(Self::rotor(rotor_type, reversed)?)?;
+        Ok(())
+    }
+
+    // positions of all five rotors, stators included
+    pub fn segments(&self) -> String {
+        return self.block.segments();
+    }
+
+    pub fn set_segments(&mut self, segments: &str) -> Result<String, Box<dyn error::Error>> {
+        return self.block.set_segments(segments);
+    }
+
+    pub fn ring_offsets(&self) -> String {
+        return self.block.ring_offsets();
+    }
+
+    pub fn set_ring_offsets(&mut self, offsets: &str) -> Result<String, Box<dyn error::Error>> {
+        return self.block.set_ring_offsets(offsets);
+    }
+
+    // encrypt single letter, without keyboard mapping
+    pub fn crypt(&mut self, ch: char) -> Result<char, Box<dyn error::Error>> {
+        let stators = self.block.stators();
+        let rotors = self.block.rotors().len() - stators;
+        if rotors != TYPEX_ROTORS || stators != TYPEX_STATORS {
+            return Err(TypexError::NotConfigured(rotors, stators).into());
+        }
+
+        return self.block.crypt(ch);
+    }
+
+    // encrypt plain text: spaces and digits are typed with shift keys, other
+    // characters that are not letters are skipped. Starts in letters shift
+    pub fn encrypt(&mut self, text: &str) -> Result<String, Box<dyn error::Error>> {
+        let mut encoded = String::new();
+        let mut figures = false;
+
+        for ch in text.chars() {
+            let val = ch.to_ascii_uppercase();
+
+            let keys = if val == ' ' {
+                vec![SPACE]
+            } else if let Some((key, _)) = FIGURES.iter().find(|(_, figure)| *figure == val) {
+                if figures {
+                    vec![*key]
+                } else {
+                    figures = true;
+                    vec![FIGURES_SHIFT, *key]
+                }
+            } else if ALPHABET.contains(val) {
+                if figures {
+                    figures = false;
+                    vec![LETTERS_SHIFT, val]
+                } else {
+                    vec![val]
+                }
+            } else {
+                continue;
+            };
+
+            for key in keys {
+                encoded.push(self.crypt(key)?);
+            }
+        }
+
+        return Ok(encoded);
+    }
+
+    // decrypt cipher text and print it like Typex printer. Starts in letters
+    // shift, characters that are not letters are skipped
+    pub fn decrypt(&mut self, text: &str) -> Result<String, Box<dyn error::Error>> {
+        let mut decoded = String::new();
+        let mut figures = false;
+
+        for ch in text.chars() {
+            if ALPHABET.contains(ch.to_ascii_uppercase()) == false {
+                continue;
+            }
+
+            let val = self.crypt(ch)?;
+            if val == SPACE {
+                decoded.push(' ');
+            } else if val == FIGURES_SHIFT {
+                figures = true;
+            } else if val == LETTERS_SHIFT {
+                figures = false;
+            } else if figures {
+                match FIGURES.iter().find(|(key, _)| *key == val) {
+                    Some((_, figure)) => decoded.push(*figure),
+                    None => decoded.push(val),
+                }
+            } else {
+                decoded.push(val);
+            }
+        }
+
+        return Ok(decoded);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::typex::Typex;
+
+    fn typex() -> Typex {
+        let mut typex = Typex::new();
+        typex.add_rotor_type("A", false).unwrap();
+        typex.add_rotor_type("B", true).unwrap();
+        typex.add_rotor_type("C", false).unwrap();
+        typex.add_stator_type("D", false).unwrap();
+        typex.add_stator_type("E", true).unwrap();
+        typex.set_segments("AOMRT").unwrap();
+        typex.set_ring_offsets("CBAAA").unwrap();
+        return typex;
+    }
+
+    #[test]
+    fn new_rotors() {
+        for rotor in ["A", "B", "C", "D", "E", "F", "G"] {
+            Typex::rotor(rotor, false).unwrap();
+            Typex::rotor(rotor, true).unwrap();
+        }
+        assert!(Typex::rotor("H", false).is_err());
+    }
+
+    #[test]
+    fn configuration() {
+        let mut typex = Typex::new();
+        typex.add_rotor_type("A", false).unwrap();
+        typex.crypt('A').unwrap_err();
+        typex.add_stator_type("B", false).unwrap();
+        typex.add_stator_type("C", false).unwrap();
+        typex.add_stator_type("D", false).unwrap_err();
+        typex.add_rotor_type("E", false).unwrap();
+        typex.add_rotor_type("F", false).unwrap();
+        typex.add_rotor_type("G", false).unwrap_err();
+
+        // moving rotors are always left from stators
+        typex.set_segments("ABCDE").unwrap();
+        typex.crypt('A').unwrap();
+        assert_eq!(typex.segments(), "ABDDE");
+    }
+
+    #[test]
+    fn stators_never_turn() {
+        let mut typex = typex();
+        for _ in 0..10000 {
+            typex.crypt('A').unwrap();
+        }
+        assert!(typex.segments().ends_with("RT"));
+    }
+
+    #[test]
+    fn no_letter_encrypts_to_itself() {
+        let mut typex = typex();
+        for ch in "ABCDEFGHIJKLMNOPQRSTUVWXYZ".repeat(20).chars() {
+            assert_ne!(typex.crypt(ch).unwrap(), ch);
+        }
+    }
+
+    #[test]
+    fn figures_shift() {
+        let mut typex = typex();
+        let encoded = typex.encrypt("Attack at 0530 hours, 12 tanks").unwrap();
+        assert_eq!(encoded.len(), "ATTACKXATXZPTEPXVHOURSXZQWXVTANKS".len());
+
+        let mut typex = self::typex();
+        assert_eq!(
+            typex.decrypt(&encoded).unwrap(),
+            "ATTACK AT 0530 HOURS 12 TANKS"
+        );
+    }
+}
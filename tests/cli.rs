@@ -0,0 +1,257 @@
+#![cfg(feature = "std")]
+#![allow(clippy::bool_comparison, clippy::needless_return)]
+
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+
+// Enigma I manual 1930, see known_answers.toml
+const SETTINGS: [&str; 10] = [
+    "--reflector",
+    "A",
+    "--rotor",
+    "II,I,III",
+    "--ring-offsets",
+    "XMV",
+    "--segments",
+    "ABL",
+    "--plug-pairs",
+    "AM FI NV PS TU WZ",
+];
+
+fn enigma(args: &[&str], input: &str) -> (bool, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_enigma"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    return (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    );
+}
+
+#[test]
+fn encrypt_and_decrypt() {
+    let mut args = vec!["encrypt"];
+    args.extend(SETTINGS);
+    let (ok, ciphertext) = enigma(&args, "Feindliche Infanterie");
+    assert!(ok);
+    assert_eq!(ciphertext.trim(), "GCDSE AHWKR HWRFB OXOFL");
+
+    let mut args = vec!["decrypt"];
+    args.extend(SETTINGS);
+    let (ok, plaintext) = enigma(&args, &ciphertext);
+    assert!(ok);
+    assert_eq!(plaintext.trim(), "FEINDLICHEINFANTERIE");
+
+    // settings before the command
+    let mut args = SETTINGS.to_vec();
+    args.extend(["encrypt", "--groups", "0"]);
+    let (ok, ciphertext) = enigma(&args, "FEIND");
+    assert!(ok);
+    assert_eq!(ciphertext.trim(), "GCDSE");
+}
+
+#[test]
+fn trace() {
+    let mut args = vec!["trace", "FE"];
+    args.extend(SETTINGS);
+    let (ok, output) = enigma(&args, "");
+    assert!(ok);
+
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines[0].starts_with("F -> G at ABM"));
+    assert!(output.contains("E -> C at ABN"));
+    assert!(output.contains("reflector"));
+
+    // without text argument letters are read from stdin
+    let mut args = vec!["trace"];
+    args.extend(SETTINGS);
+    let (ok, stdin_output) = enigma(&args, "FE\n");
+    assert!(ok);
+    assert_eq!(stdin_output, output);
+}
+
+#[test]
+fn analyze() {
+    let (ok, output) = enigma(&["analyze"], "AAAB");
+    assert!(ok);
+    assert!(output.contains("letters: 4"));
+    assert!(output.contains("index of coincidence: 0.5000"));
+
+    let (ok, output) = enigma(&["analyze", "--period", "--rotor", "I,II"], "");
+    assert!(ok);
+    assert!(output.contains("period: "));
+
+    let (ok, _) = enigma(&["analyze", "--attack", "--rotor", "I,II,III,IV,V"], "");
+    assert!(ok == false);
+}
+
+#[test]
+fn keygen() {
+    let args = ["keygen", "--machine", "m4", "--days", "3", "--seed", "1"];
+    let (ok, first) = enigma(&args, "");
+    assert!(ok);
+    assert_eq!(first.lines().count(), 3);
+    assert!(first.lines().all(|line| line.starts_with("m4 | ")));
+
+    let (_, second) = enigma(&args, "");
+    assert_eq!(first, second);
+
+    let (ok, _) = enigma(&["keygen", "--machine", "custom"], "");
+    assert!(ok == false);
+}
+
+#[test]
+fn inspect() {
+    let (ok, output) = enigma(&["inspect", "--rotor", "I,II'", "-p", "AB"], "");
+    assert!(ok);
+    assert!(output.contains("reflector: YRUHQSLDPXNGOKMIEBFZCWVJAT"));
+    assert!(output.contains("rotor 1: EKMFLGDQVZNTOWYHXUSPAIBRCJ notches: Q"));
+    assert!(output.contains("reversed"));
+    assert!(output.contains("plugboard: AB"));
+}
+
+#[test]
+fn m4() {
+    // U-264, 1942: thin rotor gets no pawl without --pawls
+    let args = [
+        "decrypt",
+        "--reflector",
+        "B-thin",
+        "--rotor",
+        "beta,II,IV,I",
+        "--ring-offsets",
+        "AAAV",
+        "--segments",
+        "VJNA",
+        "--plug-pairs",
+        "AT BL DF GJ HM NW OP QY RZ VX",
+    ];
+    let (ok, output) = enigma(
+        &args,
+        "NCZW VUSX PNYM INHZ XMQX SFWX WLKJ AHSH NMCO CCAK UQPM KCSM HKSE INJU SBLK IOSX",
+    );
+    assert!(ok);
+    assert_eq!(
+        output.trim(),
+        "VONVONJLOOKSJHFFTTTEINSEINSDREIZWOYYQNNSNEUNINHALTXXBEIANGRIFFUN"
+    );
+
+    // pawl of the left slot pushes the thin rotor only if it is given
+    let settings = ["--reflector", "B-thin", "--rotor", "beta,I,II,III"];
+    for (pawls, end) in [(None, "ARFW"), (Some("1111"), "BRFW")] {
+        let mut args = vec!["encrypt", "--format", "json", "--segments", "AQEV"];
+        args.extend(settings);
+        if let Some(pawls) = pawls {
+            args.extend(["--pawls", pawls]);
+        }
+        let (ok, output) = enigma(&args, "A");
+        assert!(ok);
+        let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(report["end"], end);
+    }
+}
+
+#[test]
+fn numbers() {
+    let args = [
+        "decrypt",
+        "--format",
+        "json",
+        "--notation",
+        "numbers",
+        "-r",
+        "II,IV,V",
+        "-o",
+        "02 21 12",
+        "-s",
+        "02 12 01",
+        "-p",
+        "AV BS CG DL FU HZ IN KM OW RX",
+    ];
+    let (ok, output) = enigma(&args, "EDPUD NRGYS");
+    assert!(ok);
+
+    let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(report["output"], "AUFKLXABTE");
+    assert_eq!(report["start"], "02 12 01");
+    assert_eq!(report["end"], "02 12 11");
+    assert_eq!(report["ring_offsets"], "02 21 12");
+
+    let (ok, output) = enigma(&["inspect", "-o", "05 17 22", "--notation", "numbers"], "");
+    assert!(ok);
+    assert!(output.contains("ring: 17 position: 01"));
+}
+
+#[test]
+fn json() {
+    let mut args = vec!["encrypt", "--format", "json"];
+    args.extend(SETTINGS);
+    let (ok, output) = enigma(&args, "FEIND, 1");
+    assert!(ok);
+
+    let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(report["output"], "GCDSE");
+    assert_eq!(report["start"], "ABL");
+    assert_eq!(report["end"], "ABQ");
+    assert_eq!(report["ring_offsets"], "XMV");
+    assert_eq!(report["plugboard"], "AM FI NV PS TU WZ");
+    assert_eq!(report["skipped"][0]["offset"], 5);
+    assert_eq!(report["skipped"][1]["character"], "1");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_enigma"))
+        .args(["--format", "json", "--rotor", "I,IX", "encrypt"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["error"]["kind"], "configuration");
+    assert!(report["error"]["message"].as_str().unwrap().contains("IX"));
+}
+
+#[test]
+fn batch() {
+    // Barbarossa, daily key of 7 July 1941
+    let args = [
+        "batch",
+        "--format",
+        "json",
+        "--rotor",
+        "II,IV,V",
+        "--ring-offsets",
+        "BUL",
+        "--segments",
+        "WXC",
+        "--plug-pairs",
+        "AV BS CG DL FU HZ IN KM OW RX",
+    ];
+    let input = "{\"id\": \"1840\", \"indicator\": \"KCH\", \"text\": \"EDPUD NRGYS\"}\n\
+                 not a record\n\
+                 {\"id\": \"1841\", \"positions\": \"BLA\", \"text\": \"EDPUD\"}\n";
+    let (ok, output) = enigma(&args, input);
+    assert!(ok);
+
+    let reports: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports[0]["key"], "BLA");
+    assert_eq!(reports[0]["output"], "AUFKLXABTE");
+    assert_eq!(reports[1]["id"], "line 2");
+    assert_eq!(reports[1]["error"]["kind"], "record");
+    assert_eq!(reports[2]["output"], "AUFKL");
+}
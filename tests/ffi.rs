@@ -0,0 +1,76 @@
+#![cfg(feature = "std")]
+#![allow(clippy::bool_comparison, clippy::needless_return)]
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+const HEADER: &str = "include/enigma.h";
+
+// header is committed, so C users don't need cbindgen. Set
+// ENIGMA_UPDATE_HEADER to regenerate it after changes of the API
+#[test]
+fn header() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(root.join("cbindgen.toml")).unwrap();
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(root.join("src/ffi.rs"))
+        .generate()
+        .unwrap()
+        .write(&mut generated);
+    let generated = String::from_utf8(generated).unwrap();
+
+    if env::var_os("ENIGMA_UPDATE_HEADER").is_some() {
+        fs::write(root.join(HEADER), &generated).unwrap();
+    }
+    let committed = fs::read_to_string(root.join(HEADER)).unwrap();
+    assert!(
+        generated == committed,
+        "{} is outdated, run tests with ENIGMA_UPDATE_HEADER=1",
+        HEADER
+    );
+}
+
+// cdylib of tests is built into `deps` directory, next to the tests
+fn library_dir() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    return exe.parent().unwrap().to_path_buf();
+}
+
+#[cfg(unix)]
+#[test]
+fn c_program() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let library = library_dir();
+    let program = env::temp_dir().join(format!("enigma-ffi-{}", std::process::id()));
+    let compiler = env::var("CC").unwrap_or("cc".to_string());
+
+    let status = Command::new(compiler)
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg(root.join("tests/ffi/main.c"))
+        .arg("-o")
+        .arg(&program)
+        .arg("-L")
+        .arg(&library)
+        .arg(format!("-Wl,-rpath,{}", library.display()))
+        .arg("-lenigma")
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(&program).output().unwrap();
+    _ = fs::remove_file(&program);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "AUFKLXABTE\n");
+}
@@ -0,0 +1,53 @@
+/* Test program of the C API, built and run by tests/ffi.rs */
+
+#include <enigma.h>
+#include <stdio.h>
+#include <string.h>
+
+#define CHECK(condition)                                                      \
+  if (!(condition)) {                                                         \
+    fprintf(stderr, "%s:%d: check failed: %s: %s\n", __FILE__, __LINE__,      \
+            #condition, enigma_last_error());                                 \
+    return 1;                                                                 \
+  }
+
+int main(void) {
+  const char *ciphertext = "EDPUD NRGYS";
+  char output[16];
+  char positions[8];
+  size_t written = 0;
+
+  EnigmaDevice *device = enigma_device_new();
+  CHECK(device != NULL);
+
+  /* Barbarossa, 7 July 1941 */
+  CHECK(enigma_device_configure(
+            device, "enigma-i | B | II IV V | BUL | BLA | "
+                    "AV BS CG DL FU HZ IN KM OW RX") == ENIGMA_STATUS_OK);
+
+  /* too small buffer doesn't move rotors */
+  CHECK(enigma_device_crypt(device, ciphertext, strlen(ciphertext), output, 4,
+                            &written) == ENIGMA_STATUS_BUFFER_TOO_SMALL);
+  CHECK(written == 11);
+
+  CHECK(enigma_device_crypt(device, ciphertext, strlen(ciphertext), output,
+                            sizeof(output), &written) == ENIGMA_STATUS_OK);
+  CHECK(written == 10);
+
+  CHECK(enigma_device_positions(device, positions, sizeof(positions)) ==
+        ENIGMA_STATUS_OK);
+  CHECK(strcmp(positions, "BLK") == 0);
+
+  CHECK(enigma_device_set_positions(device, "BL!") ==
+        ENIGMA_STATUS_INVALID_SETTINGS);
+  CHECK(strlen(enigma_last_error()) > 0);
+  CHECK(enigma_device_set_positions(device, "BLA") == ENIGMA_STATUS_OK);
+
+  CHECK(enigma_device_configure(device, "m3 | B | I II") ==
+        ENIGMA_STATUS_INVALID_SETTINGS);
+  CHECK(enigma_device_configure(NULL, "") == ENIGMA_STATUS_NULL_POINTER);
+
+  enigma_device_free(device);
+  printf("%s\n", output);
+  return 0;
+}
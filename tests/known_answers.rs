@@ -0,0 +1,210 @@
+#![cfg(feature = "std")]
+#![allow(clippy::bool_comparison, clippy::needless_return)]
+
+use enigma::device::Device;
+use enigma_core::machine::Enigma;
+use enigma_core::plugboard::PlugBoard;
+use enigma_core::reflector::Reflector;
+use enigma_core::rotor::Rotor;
+use serde::Deserialize;
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+
+// see known_answers.toml
+#[derive(Deserialize)]
+struct KnownAnswers {
+    message: Vec<Message>,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    name: String,
+    reflector: String,
+    rotors: Vec<String>,
+    #[serde(default)]
+    pawls: String, // like "0111", all slots have pawl if not set
+    ring_offsets: String,
+    #[serde(default)]
+    grundstellung: String,
+    #[serde(default)]
+    indicator: String,
+    segments: String,
+    cables: String,
+    ciphertext: String,
+    plaintext: String,
+}
+
+impl Message {
+    fn pawls(&self) -> Option<Vec<bool>> {
+        if self.pawls.is_empty() {
+            return None;
+        }
+        return Some(self.pawls.chars().map(|ch| ch == '1').collect());
+    }
+
+    fn device(&self) -> Device {
+        let mut device = Device::new();
+        device.set_reflector_type(&self.reflector).unwrap();
+        for rotor in self.rotors.iter() {
+            device.add_rotor_type(rotor).unwrap();
+        }
+        if let Some(pawls) = self.pawls() {
+            device.set_pawls(&pawls).unwrap();
+        }
+        device.set_ring_offsets(&self.ring_offsets).unwrap();
+        device.set_segments(&self.segments).unwrap();
+        device.set_cables(&self.cables, Some(13)).unwrap();
+        return device;
+    }
+}
+
+fn enigma(message: &Message, input: &str) -> String {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_enigma"));
+    if message.pawls.is_empty() == false {
+        command.args(["--pawls", &message.pawls]);
+    }
+    let mut child = command
+        .args(["--reflector", &message.reflector])
+        .args(["--rotor", &message.rotors.join(",")])
+        .args(["--ring-offsets", &message.ring_offsets])
+        .args(["--segments", &message.segments])
+        .args(["--plug-pairs", &message.cables])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{}", message.name);
+    return String::from_utf8(output.stdout).unwrap().trim().to_string();
+}
+
+fn core_machine<const N: usize>(message: &Message) -> Enigma<N> {
+    let rotors: [Rotor; N] =
+        std::array::from_fn(|slot| Rotor::model(&message.rotors[slot]).unwrap());
+    let mut enigma = Enigma::new(
+        Reflector::model(&message.reflector).unwrap(),
+        rotors,
+        PlugBoard::with_pairs(&message.cables).unwrap(),
+    );
+    if let Some(pawls) = message.pawls() {
+        enigma.set_pawls(std::array::from_fn(|slot| pawls[slot]));
+    }
+    enigma.set_ring_offsets(&message.ring_offsets).unwrap();
+    enigma.set_positions(&message.segments).unwrap();
+    return enigma;
+}
+
+// same message by the core without std
+fn core<const N: usize>(message: &Message, input: &str) -> String {
+    let mut enigma = core_machine::<N>(message);
+    let mut output = vec![0; input.len()];
+    let written = enigma.crypt(input.as_bytes(), &mut output).unwrap();
+    output.truncate(written);
+    return String::from_utf8(output).unwrap();
+}
+
+#[test]
+fn known_answers() {
+    let corpus: KnownAnswers = toml::from_str(include_str!("known_answers.toml")).unwrap();
+    assert!(corpus.message.len() >= 4);
+
+    for message in corpus.message.iter() {
+        if message.indicator.is_empty() == false {
+            let mut device = message.device();
+            device.set_segments(&message.grundstellung).unwrap();
+            let key: String = message
+                .indicator
+                .chars()
+                .map(|ch| device.crypt(ch).unwrap())
+                .collect();
+            assert_eq!(key, message.segments, "{}", message.name);
+        }
+
+        let ciphertext: String = message
+            .ciphertext
+            .chars()
+            .filter(|ch| ch.is_whitespace() == false)
+            .collect();
+
+        let mut device = message.device();
+        let plaintext: String = ciphertext
+            .chars()
+            .map(|ch| device.crypt(ch).unwrap())
+            .collect();
+        assert_eq!(plaintext, message.plaintext, "{}", message.name);
+
+        let mut device = message.device();
+        assert_eq!(
+            device.crypt_parallel(&message.plaintext, 3),
+            ciphertext,
+            "{}",
+            message.name
+        );
+
+        // groups and line breaks of the cipher text are skipped by the machine
+        assert_eq!(
+            enigma(message, &message.ciphertext),
+            message.plaintext,
+            "{}",
+            message.name
+        );
+        assert_eq!(
+            enigma(message, &message.plaintext),
+            ciphertext,
+            "{}",
+            message.name
+        );
+
+        let plaintext = match message.rotors.len() {
+            3 => core::<3>(message, &message.ciphertext),
+            _ => core::<4>(message, &message.ciphertext),
+        };
+        assert_eq!(plaintext, message.plaintext, "{}", message.name);
+    }
+}
+
+// positions after every letter, by the core
+fn core_positions<const N: usize>(message: &Message, count: usize) -> Vec<String> {
+    let mut enigma = core_machine::<N>(message);
+    let mut output = [0; 1];
+    let mut positions = Vec::new();
+    for _ in 0..count {
+        enigma.crypt(b"A", &mut output).unwrap();
+        positions.push(String::from_utf8(enigma.positions().to_vec()).unwrap());
+    }
+    return positions;
+}
+
+// Device and the core machine share rotors and stepping, but layouts of pawls
+// are set up by each, so stepping of both is compared letter by letter, long
+// enough for every rotor to pass its notch
+#[test]
+fn stepping() {
+    let corpus: KnownAnswers = toml::from_str(include_str!("known_answers.toml")).unwrap();
+    let count = 26 * 26 + 26;
+
+    for message in corpus.message.iter() {
+        let mut device = message.device();
+        let positions: Vec<String> = (0..count)
+            .map(|_| {
+                device.crypt('A').unwrap();
+                device.segments()
+            })
+            .collect();
+
+        let expected = match message.rotors.len() {
+            3 => core_positions::<3>(message, count),
+            _ => core_positions::<4>(message, count),
+        };
+        assert_eq!(positions, expected, "{}", message.name);
+    }
+}
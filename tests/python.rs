@@ -0,0 +1,35 @@
+#![cfg(feature = "python")]
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+// Extension module is the cdylib of the crate, it is copied with the module
+// name, so locally installed python can import it
+#[test]
+fn python_module() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let library = env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("libenigma.so");
+    let module_dir = env::temp_dir().join(format!("enigma-python-{}", std::process::id()));
+    fs::create_dir_all(&module_dir).unwrap();
+    fs::copy(library, module_dir.join("enigma.so")).unwrap();
+
+    let python = env::var("PYTHON").unwrap_or("python3".to_string());
+    let output = Command::new(python)
+        .arg(root.join("tests/python/test_enigma.py"))
+        .env("PYTHONPATH", &module_dir)
+        .output()
+        .unwrap();
+    _ = fs::remove_dir_all(&module_dir);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,77 @@
+# Tests of the Python module, run by tests/python.rs with `python` feature
+
+import unittest
+
+import enigma
+
+# Barbarossa, 7 July 1941
+BARBAROSSA = dict(
+    rotors=["II", "IV", "V"],
+    ring_offsets="BUL",
+    positions="BLA",
+    plug_pairs="AV BS CG DL FU HZ IN KM OW RX",
+)
+
+
+class DeviceTest(unittest.TestCase):
+    def test_encrypt(self):
+        device = enigma.Device(**BARBAROSSA)
+        self.assertEqual(device.decrypt("EDPUD NRGYS"), "AUFKLXABTE")
+        self.assertEqual(device.positions, "BLK")
+
+        device.positions = "BLA"
+        self.assertEqual(device.encrypt("AUFKL"), "EDPUD")
+        self.assertEqual(device.ring_offsets, "BUL")
+        self.assertEqual(device.plug_pairs, BARBAROSSA["plug_pairs"])
+
+    def test_defaults(self):
+        device = enigma.Device()
+        self.assertEqual(device.encrypt("AAAAA"), "BDZGO")
+        self.assertEqual([rotor.wiring[0] for rotor in device.rotors], ["E", "A", "B"])
+        self.assertEqual(device.reflector.wiring, "YRUHQSLDPXNGOKMIEBFZCWVJAT")
+
+    def test_parts(self):
+        rotors = [enigma.Rotor.model(name) for name in ["I", "II", "III"]]
+        device = enigma.Device.from_parts(
+            enigma.Reflector.model("B"), rotors, enigma.PlugBoard("AB")
+        )
+        self.assertEqual(len(device.encrypt("Hello, world!")), 10)
+
+        rotor = enigma.Rotor("EKMFLGDQVZNTOWYHXUSPAIBRCJ", notches="Q", position="Q")
+        self.assertTrue(rotor.advance())
+        self.assertEqual(rotor.position, "R")
+        self.assertTrue(rotor.reversed().is_reversed)
+
+        board = enigma.PlugBoard()
+        board.connect("A", "Z")
+        self.assertEqual(board.swap("Z"), "A")
+        self.assertEqual(board.disconnect("A"), ("A", "Z"))
+
+    def test_trace(self):
+        device = enigma.Device()
+        traces = list(device.trace("AB!"))
+        self.assertEqual(len(traces), 2)
+        self.assertEqual(traces[0].input, "A")
+        self.assertEqual(traces[0].output, "B")
+        self.assertEqual(traces[0].positions, "AAB")
+        self.assertEqual(traces[0].steps[4][0], "reflector")
+        self.assertTrue(str(traces[1]).startswith("B -> "))
+        self.assertEqual(device.positions, "AAC")
+
+    def test_errors(self):
+        with self.assertRaises(enigma.SettingsError):
+            enigma.Device(rotors=["I", "II"])
+        with self.assertRaises(enigma.SettingsError):
+            enigma.Device(machine="m5")
+        with self.assertRaises(enigma.PlugboardError):
+            enigma.PlugBoard("AA")
+        with self.assertRaises(enigma.SettingsError):
+            enigma.Device().positions = "A1"
+        with self.assertRaises(enigma.EnigmaError):
+            enigma.Reflector("ABC")
+        with self.assertRaises(ValueError):
+            enigma.Rotor.model("IX")
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,129 @@
+#![cfg(feature = "std")]
+#![allow(clippy::bool_comparison, clippy::needless_return)]
+
+use serde_json::json;
+use serde_json::Value;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::process::Child;
+use std::process::Command;
+use std::process::Stdio;
+
+// server is killed on drop, so failed test doesn't leave it running
+struct Server {
+    child: Child,
+    address: String,
+}
+
+impl Server {
+    fn start(args: &[&str]) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_enigma"))
+            .arg("serve")
+            .args(args)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let address = line
+            .trim()
+            .strip_prefix("listening on ")
+            .unwrap()
+            .to_string();
+
+        return Self { child, address };
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        _ = self.child.kill();
+        _ = self.child.wait();
+    }
+}
+
+struct Client<S: Read + Write> {
+    reader: BufReader<S>,
+}
+
+impl<S: Read + Write> Client<S> {
+    fn request(&mut self, request: Value) -> Value {
+        let stream = self.reader.get_mut();
+        stream
+            .write_all(format!("{}\n", request).as_bytes())
+            .unwrap();
+
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        return serde_json::from_str(&line).unwrap();
+    }
+}
+
+// Barbarossa, message key BLA
+fn session<S: Read + Write>(client: &mut Client<S>) {
+    let response = client.request(json!({
+        "op": "create",
+        "settings": {
+            "rotors": ["II", "IV", "V"],
+            "ring_offsets": "BUL",
+            "positions": "BLA",
+            "plug_pairs": "AV BS CG DL FU HZ IN KM OW RX",
+        },
+    }));
+    assert_eq!(response["ok"], true);
+    let session = response["session"].clone();
+
+    let response = client.request(json!({ "op": "encrypt", "session": session, "text": "EDPUD" }));
+    assert_eq!(response["output"], "AUFKL");
+
+    let response = client.request(json!({ "op": "state", "session": session }));
+    assert_eq!(response["positions"], "BLF");
+
+    let response =
+        client.request(json!({ "op": "set_positions", "session": session, "positions": "BLA" }));
+    assert_eq!(response["ok"], true);
+    let response = client.request(json!({ "op": "encrypt", "session": session, "text": "EDPUD" }));
+    assert_eq!(response["output"], "AUFKL");
+
+    let response = client.request(json!({ "op": "close", "session": session }));
+    assert_eq!(response["ok"], true);
+    let response = client.request(json!({ "op": "state", "session": session }));
+    assert_eq!(response["ok"], false);
+}
+
+#[test]
+fn tcp() {
+    let server = Server::start(&["--tcp", "127.0.0.1:0"]);
+    let mut client = Client {
+        reader: BufReader::new(TcpStream::connect(&server.address).unwrap()),
+    };
+    session(&mut client);
+
+    let response = client.request(json!({ "op": "encrypt", "session": 100, "text": "A" }));
+    assert_eq!(response["ok"], false);
+    assert!(response["error"].as_str().unwrap().contains("100"));
+}
+
+#[cfg(unix)]
+#[test]
+fn unix() {
+    let path = std::env::temp_dir().join(format!("enigma-{}.sock", std::process::id()));
+    _ = std::fs::remove_file(&path);
+
+    let server = Server::start(&["--unix", path.to_str().unwrap()]);
+    let mut client = Client {
+        reader: BufReader::new(UnixStream::connect(&server.address).unwrap()),
+    };
+    session(&mut client);
+
+    drop(server);
+    _ = std::fs::remove_file(&path);
+}